@@ -0,0 +1,119 @@
+//! Native Open and Save file dialogs, shown by the desktop's own dialog
+//! tool: zenity or kdialog on Linux, AppleScript on macOS and PowerShell on
+//! Windows. The dialogs block, so they are run on tokio's blocking pool and
+//! meant to be awaited in a `Command`.
+//!
+//! Both return `Ok(None)` when the user cancels, and an error when no
+//! dialog could be shown at all.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Asks for a graph to open.
+pub async fn open_file() -> Result<Option<PathBuf>, String>{
+    run(|| pick(Mode::Open, None)).await
+}
+
+/// Asks where to save the graph, starting from `current` if the graph
+/// already has a file. `.json` is appended to names without an extension.
+pub async fn save_file(current: Option<PathBuf>) -> Result<Option<PathBuf>, String>{
+    let path = run(move || pick(Mode::Save, current.as_deref())).await?;
+    Ok(path.map(|path| match path.extension() {
+        Some(_) => path,
+        None => path.with_extension("json"),
+    }))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode{
+    Open,
+    Save,
+}
+
+async fn run(
+    pick: impl FnOnce() -> Result<Option<PathBuf>, String> + Send + 'static,
+) -> Result<Option<PathBuf>, String>{
+    tokio::task::spawn_blocking(pick).await
+        .map_err(|error| format!("the file dialog failed: {}", error))?
+}
+
+/// Tries the dialog programs of the platform in turn, returning the result
+/// of the first one that could be started.
+fn pick(mode: Mode, current: Option<&Path>) -> Result<Option<PathBuf>, String>{
+    for mut command in commands(mode, current) {
+        let output = match command.output() {
+            Ok(output) => output,
+            Err(_) => continue,
+        };
+        // Every tool exits with a failure status when cancelled.
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        return Ok(Some(PathBuf::from(path)).filter(|path| !path.as_os_str().is_empty()));
+    }
+    Err(String::from("no file dialog is available (install zenity or kdialog)"))
+}
+
+#[cfg(target_os = "macos")]
+fn commands(mode: Mode, current: Option<&Path>) -> Vec<Command>{
+    let script = match (mode, current.and_then(Path::file_name)) {
+        (Mode::Open, _) => String::from("POSIX path of (choose file of type {\"json\"} with prompt \"Open graph\")"),
+        (Mode::Save, name) => format!(
+            "POSIX path of (choose file name with prompt \"Save graph\" default name \"{}\")",
+            name.map_or_else(|| String::from("untitled.json"), |name| name.to_string_lossy().replace('"', "")),
+        ),
+    };
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(script);
+    vec![command]
+}
+
+#[cfg(windows)]
+fn commands(mode: Mode, current: Option<&Path>) -> Vec<Command>{
+    let (class, title) = match mode {
+        Mode::Open => ("OpenFileDialog", "Open graph"),
+        Mode::Save => ("SaveFileDialog", "Save graph"),
+    };
+    let name = current.map_or_else(String::new, |path| path.display().to_string().replace('\'', "''"));
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms; \
+         $dialog = New-Object System.Windows.Forms.{}; \
+         $dialog.Title = '{}'; \
+         $dialog.Filter = 'JSON graphs (*.json)|*.json|All files (*.*)|*.*'; \
+         $dialog.FileName = '{}'; \
+         if ($dialog.ShowDialog() -eq 'OK') {{ $dialog.FileName }} else {{ exit 1 }}",
+        class, title, name,
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-Command", &script]);
+    vec![command]
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn commands(mode: Mode, current: Option<&Path>) -> Vec<Command>{
+    let mut zenity = Command::new("zenity");
+    zenity.args(["--file-selection", "--file-filter=JSON graphs | *.json", "--file-filter=All files | *"]);
+    let mut kdialog = Command::new("kdialog");
+    match mode {
+        Mode::Open => {
+            zenity.arg("--title=Open graph");
+            kdialog.args(["--title", "Open graph", "--getopenfilename", ".", "*.json"]);
+        }
+        Mode::Save => {
+            zenity.args(["--title=Save graph", "--save", "--confirm-overwrite"]);
+            kdialog.args(["--title", "Save graph", "--getsavefilename"]);
+            match current {
+                Some(path) => {
+                    zenity.arg(format!("--filename={}", path.display()));
+                    kdialog.arg(path);
+                }
+                None => {
+                    kdialog.arg(".");
+                }
+            }
+            kdialog.arg("*.json");
+        }
+    }
+    vec![zenity, kdialog]
+}
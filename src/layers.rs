@@ -0,0 +1,98 @@
+use iced::{button, text_input, Button, Checkbox, Column, Element, Length, Row, Text, TextInput};
+
+use crate::network::Network;
+
+/// Side panel listing the layers, top layer first, with their visibility
+/// and lock. A new layer is added by name, and the selection can be moved
+/// onto any layer.
+pub struct LayerPanel{
+    rows: Vec<(String, button::State)>,
+    new_name: text_input::State,
+    new_name_text: String,
+    add: button::State,
+}
+
+#[derive(Debug, Clone)]
+pub enum LayerMessage{
+    Visible(String, bool),
+    Locked(String, bool),
+    /// Moves the selected nodes and edges onto the layer.
+    MoveSelection(String),
+    NewName(String),
+    Add,
+}
+
+impl LayerPanel{
+    pub const WIDTH: u16 = 260;
+    const TEXT_SIZE: u16 = 16;
+
+    pub fn new() -> Self{
+        LayerPanel{
+            rows: Vec::new(),
+            new_name: text_input::State::new(),
+            new_name_text: String::new(),
+            add: button::State::new(),
+        }
+    }
+
+    pub fn update(&mut self, network: &mut Network, message: LayerMessage){
+        match message {
+            LayerMessage::Visible(name, visible) => network.set_layer_visible(&name, visible),
+            LayerMessage::Locked(name, locked) => network.set_layer_locked(&name, locked),
+            LayerMessage::MoveSelection(name) => {
+                network.move_selection_to_layer(&name);
+            }
+            LayerMessage::NewName(text) => self.new_name_text = text,
+            LayerMessage::Add => {
+                if network.add_layer(&self.new_name_text) {
+                    self.new_name_text.clear();
+                }
+            }
+        }
+    }
+
+    pub fn view(&mut self, network: &Network) -> Element<'_, LayerMessage>{
+        let layers = network.layers();
+        if !layers.iter().rev().map(|layer| &layer.name).eq(self.rows.iter().map(|(name, _)| name)) {
+            self.rows = layers.iter().rev().map(|layer| (layer.name.clone(), button::State::new())).collect();
+        }
+
+        let size = Self::TEXT_SIZE;
+        let mut content = Column::new()
+            .width(Length::Units(Self::WIDTH))
+            .padding(10)
+            .spacing(6)
+            .push(Text::new("Layers").size(size + 2));
+        for ((name, state), layer) in self.rows.iter_mut().zip(layers.iter().rev()) {
+            let (visible, locked) = (layer.visible, layer.locked);
+            let visible_name = name.clone();
+            let locked_name = name.clone();
+            content = content.push(Row::new()
+                .spacing(6)
+                .push(Checkbox::new(visible, name.as_str(), move |visible| {
+                    LayerMessage::Visible(visible_name.clone(), visible)
+                }).size(size).text_size(size).width(Length::Fill))
+                .push(Checkbox::new(locked, "Lock", move |locked| {
+                    LayerMessage::Locked(locked_name.clone(), locked)
+                }).size(size).text_size(size))
+                .push(Button::new(state, Text::new("←").size(size))
+                    .on_press(LayerMessage::MoveSelection(name.clone()))));
+        }
+        content
+            .push(Row::new()
+                .spacing(6)
+                .push(TextInput::new(&mut self.new_name, "New layer", &self.new_name_text, LayerMessage::NewName)
+                    .size(size)
+                    .padding(4)
+                    .on_submit(LayerMessage::Add))
+                .push(Button::new(&mut self.add, Text::new("+").size(size))
+                    .on_press(LayerMessage::Add)))
+            .into()
+    }
+}
+
+impl Default for LayerPanel{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,8 @@
+pub mod dialog;
+pub mod layers;
+pub mod live;
+pub mod network;
+pub mod panel;
+pub mod properties;
+pub mod session;
+pub mod tags;
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use iced_native::futures::StreamExt;
+use iced_native::subscription::{self, Subscription};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::network::NetworkMessage;
+
+/// Delay before the first reconnection attempt. Doubles after every failed
+/// attempt, up to `MAX_BACKOFF`.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionStatus{
+    Connecting,
+    Connected,
+    Disconnected{ retry_in: Duration },
+}
+
+#[derive(Debug)]
+pub enum LiveEvent{
+    Status(ConnectionStatus),
+    /// A change pushed by the server, to apply with `Network::update_message`.
+    Message(NetworkMessage),
+}
+
+enum State{
+    Connecting{ backoff: Duration },
+    Connected(Box<WebSocketStream<MaybeTlsStream<TcpStream>>>),
+    Waiting{ backoff: Duration },
+}
+
+/// Listens to a WebSocket that pushes graph changes as JSON text messages,
+/// e.g. `{"type": "node_moved", "id": 3, "x": 10, "y": 20}`. Reconnects with
+/// exponential backoff whenever the connection fails or drops.
+pub fn connect(url: String) -> Subscription<LiveEvent>{
+    struct Live;
+    let id = (std::any::TypeId::of::<Live>(), url.clone());
+    subscription::unfold(id, State::Connecting{ backoff: INITIAL_BACKOFF }, move |state| {
+        next(url.clone(), state)
+    })
+}
+
+async fn next(url: String, state: State) -> (Option<LiveEvent>, State){
+    match state {
+        State::Connecting{ backoff } => match connect_async(url.as_str()).await {
+            Ok((socket, _)) => (
+                Some(LiveEvent::Status(ConnectionStatus::Connected)),
+                State::Connected(Box::new(socket)),
+            ),
+            Err(error) => {
+                println!("Could not connect to {}: {}", url, error);
+                (
+                    Some(LiveEvent::Status(ConnectionStatus::Disconnected{ retry_in: backoff })),
+                    State::Waiting{ backoff },
+                )
+            }
+        },
+        State::Waiting{ backoff } => {
+            tokio::time::sleep(backoff).await;
+            (
+                Some(LiveEvent::Status(ConnectionStatus::Connecting)),
+                State::Connecting{ backoff: (backoff * 2).min(MAX_BACKOFF) },
+            )
+        }
+        State::Connected(mut socket) => match socket.next().await {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                Ok(message) => (Some(LiveEvent::Message(message)), State::Connected(socket)),
+                Err(error) => {
+                    println!("Ignoring live event {}: {}", text, error);
+                    (None, State::Connected(socket))
+                }
+            },
+            Some(Ok(_)) => (None, State::Connected(socket)),
+            Some(Err(_)) | None => (
+                Some(LiveEvent::Status(ConnectionStatus::Disconnected{ retry_in: INITIAL_BACKOFF })),
+                State::Waiting{ backoff: INITIAL_BACKOFF },
+            ),
+        },
+    }
+}
@@ -1,17 +1,51 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
-use iced::{Application, Column, Container, Element, Length, Point, Rectangle, Renderer, Settings, time, window};
-use iced_native::{Command, Layout, renderer, Subscription, Widget};
-use iced_native::layout::{Limits, Node};
-use iced_native::renderer::Style;
-use crate::AppMessage::Network;
-use crate::network::NetworkMessage;
+use iced::{button, Alignment, Application, Button, Column, Container, Element, Length, Row, Settings, Text, time, window};
+use iced_native::{keyboard, Command, Event, Subscription};
+use iced_native::subscription;
+use iced_native::window::Event as WindowEvent;
+use sword::dialog;
+use sword::layers::{LayerMessage, LayerPanel};
+use sword::live::{self, ConnectionStatus, LiveEvent};
+use sword::network;
+use sword::network::{Camera, LoadError, NetworkMessage, NodeTemplate, SerNetwork};
+use sword::panel::Panel;
+use sword::properties::{PropertiesMessage, PropertiesPanel};
+use sword::session::Session;
+use sword::tags::{TagMessage, TagPanel};
 
 fn main() -> iced::Result {
     println!("Init");
+    // `--tick-rate=<hz>` overrides how often animations are advanced,
+    // `--load-url=<url>` fetches a JSON graph on startup and
+    // `--live-url=<ws://...>` applies the changes pushed by a WebSocket.
+    // `--recover` restores the work autosaved before an unclean exit.
+    // `--export-scale=<n>` makes the Ctrl+P image `n` times the canvas size.
+    let tick_interval = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--tick-rate=")?.parse::<u64>().ok())
+        .filter(|rate| *rate > 0)
+        .map(|rate| Duration::from_millis(1000 / rate));
+    let url = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--load-url=").map(String::from));
+    let live_url = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--live-url=").map(String::from));
+    let recover = std::env::args().any(|arg| arg == "--recover");
+    let export_scale = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--export-scale=")?.parse::<f32>().ok());
+    let session = Session::load(Session::PATH);
+    let window_size = session.as_ref()
+        .map_or(window::Settings::default().size, |session| session.window_size);
+    let position = match session.as_ref().and_then(Session::on_screen_position) {
+        Some((x, y)) => window::Position::Specific(x, y),
+        None => window::Position::Centered,
+    };
     App::run(Settings{
         window: window::Settings{
-            position: window::Position::Centered,
-            resizable: false,
+            size: window_size,
+            position,
+            resizable: true,
+            min_size: Some(App::MIN_WINDOW_SIZE),
             decorations: true,
             transparent: false,
             always_on_top: false,
@@ -19,388 +53,554 @@ fn main() -> iced::Result {
             ..window::Settings::default()
         },
         antialiasing: true,
-        exit_on_close_request: true,
+        // The session is saved when the close request comes in.
+        exit_on_close_request: false,
+        flags: AppFlags{ tick_interval, url, live_url, session, recover, export_scale },
         ..Settings::default()
     })
 }
 
 struct App{
     network: network::Network,
+    tick_interval: Duration,
+    /// Kept here so it carries over to networks that are opened later.
+    export_scale: f32,
+    /// Message shown under the canvas, e.g. why a load failed.
+    status: Option<String>,
+    /// WebSocket pushing live changes to the network, if any.
+    live_url: Option<String>,
+    live_status: ConnectionStatus,
+    /// Number of selected nodes, as last reported by the canvas.
+    selected: usize,
+    properties: PropertiesPanel,
+    tags: TagPanel,
+    layers: LayerPanel,
+    /// Node kinds offered by the palette, and the one being dragged.
+    palette: Vec<NodeTemplate>,
+    dragging_template: Option<usize>,
+    /// Last known window geometry, saved with the session on exit.
+    window_position: Option<(i32, i32)>,
+    window_size: (u32, u32),
+    exiting: bool,
+    /// When the network was last autosaved, and at which revision.
+    autosaved_at: Instant,
+    autosaved_revision: u64,
+    /// Recovery file left by a previous run, until it is restored or
+    /// discarded. Autosaves go next to it while it is waiting, see
+    /// `autosave_path`.
+    pending_recovery: Option<PathBuf>,
+    restore_button: button::State,
+    discard_button: button::State,
+}
+
+#[derive(Default)]
+struct AppFlags{
+    /// Tick interval to use instead of the default.
+    tick_interval: Option<Duration>,
+    /// Graph to load on startup.
+    url: Option<String>,
+    live_url: Option<String>,
+    /// Window geometry and view from the previous run.
+    session: Option<Session>,
+    /// Restore the autosave of a previous run.
+    recover: bool,
+    /// Resolution of the Ctrl+P export, as a multiple of the canvas size.
+    export_scale: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RecoveryChoice{
+    Restore,
+    Discard,
 }
 
 #[derive(Debug)]
 enum AppMessage{
     Tick(Instant),
+    /// Sent by the canvas, which already applied whatever it reports.
     Network(NetworkMessage),
+    /// Change pushed by the live connection, to apply to the network.
+    Live(NetworkMessage),
+    LoadUrl(String),
+    GraphLoaded(Result<Box<network::Network>, LoadError>),
+    LiveStatus(ConnectionStatus),
+    Window(WindowEvent),
+    Autosaved(Result<(), String>),
+    /// Answer to the prompt about a recovery file left by a crash.
+    Recovery(RecoveryChoice),
+    /// Ctrl+O: pick a graph to open.
+    Open,
+    /// Ctrl+S, or Ctrl+Shift+S with `choose` to always pick a new file.
+    Save{ choose: bool },
+    /// Ctrl+Shift+O: pick a graph to merge into the current one.
+    Import,
+    /// The file picked in the Open or Save dialog, `None` if cancelled.
+    OpenPicked(Result<Option<PathBuf>, String>),
+    ImportPicked(Result<Option<PathBuf>, String>),
+    GraphImported(Result<Box<network::Network>, LoadError>),
+    SavePicked(Result<Option<PathBuf>, String>),
+    /// Ctrl+Shift+E: save the selected nodes and the edges between them
+    /// to a file of their own.
+    ExportSelection,
+    ExportPicked(Result<Option<PathBuf>, String>),
+    Exported(Result<(), String>),
+    /// A save finished, the network having been at `revision` when it
+    /// started.
+    Saved{ path: PathBuf, revision: u64, result: Result<(), String> },
+    Properties(PropertiesMessage),
+    Tags(TagMessage),
+    Layers(LayerMessage),
+    /// A palette template was picked up, or `None` when it was let go.
+    PaletteDrag(Option<usize>),
 }
 
-impl iced::Application for App{
-    type Executor = iced::executor::Default;
-    type Message = AppMessage;
-    type Flags = ();
-
-    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
-        (Self{
-            network: network::Network::new(),
-        }, Command::none())
-    }
-
-    fn title(&self) -> String {
-        String::from("Sword")
+impl App{
+    const DEFAULT_TICK_INTERVAL: Duration = Duration::from_millis(1000 / 100);
+    const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+    /// Room for the palette, the properties panel and some canvas.
+    const MIN_WINDOW_SIZE: (u32, u32) = (
+        Panel::<AppMessage>::WIDTH as u32 + PropertiesPanel::WIDTH as u32 + 300,
+        400,
+    );
+
+    /// Writes the network to its recovery file in the background if it
+    /// changed since the last autosave.
+    fn autosave(&mut self, now: Instant) -> Command<AppMessage>{
+        if !self.network.is_dirty()
+            || self.network.revision() == self.autosaved_revision
+        {
+            return Command::none();
+        }
+        self.autosaved_at = now;
+        self.autosaved_revision = self.network.revision();
+        let graph = SerNetwork::from(&self.network);
+        Command::perform(
+            network::Network::write_json(graph, self.autosave_path()),
+            AppMessage::Autosaved,
+        )
     }
 
-    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
-        match message {
-            AppMessage::Tick(_) => {}
-            AppMessage::Network(_) => {}
+    /// Where the current work is autosaved: the network's recovery file,
+    /// or a file next to it while the old one is still waiting to be
+    /// restored or discarded.
+    fn autosave_path(&self) -> PathBuf{
+        let path = self.network.recovery_path();
+        if self.pending_recovery.as_ref() == Some(&path) {
+            aside(&path)
+        } else {
+            path
         }
-        Command::none()
     }
 
-    fn subscription(&self) -> Subscription<Self::Message> {
-        time::every(Duration::from_millis(1000 / 100))
-            .map(AppMessage::Tick)
+    /// Writes the network to `path` in the background.
+    fn save_to(&mut self, path: PathBuf) -> Command<AppMessage>{
+        self.status = Some(format!("Saving {}…", path.display()));
+        let graph = SerNetwork::from(&self.network);
+        let revision = self.network.revision();
+        Command::perform(
+            network::Network::write_json(graph, path.clone()),
+            move |result| AppMessage::Saved{ path: path.clone(), revision, result },
+        )
     }
 
-    fn view(&mut self) -> Element<'_, Self::Message> {
-        let content = Column::new()
-            .push(self.network
-                .view()
-                .map(move |message| Network(message))
-            );
-        Container::new(content)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .into()
+    /// Replaces the network with the work in the pending recovery file. A
+    /// recovery file that can't be read stays pending, so it can still be
+    /// discarded.
+    fn restore_recovery(&mut self){
+        let path = match self.pending_recovery.clone() {
+            Some(path) => path,
+            None => return,
+        };
+        if !path.exists() {
+            self.pending_recovery = None;
+            self.status = Some(format!("{} no longer exists", path.display()));
+            return;
+        }
+        // The recovery file is named after the file it belongs to.
+        let file = self.network.current_path()
+            .filter(|_| path == self.network.recovery_path())
+            .map(|file| file.to_path_buf());
+        match network::Network::load_recovery(&path, file) {
+            Ok(network) => {
+                self.network = network;
+                self.network.set_export_scale(self.export_scale);
+                self.selected = 0;
+                self.pending_recovery = None;
+                self.status = Some(format!("Restored unsaved work from {}", path.display()));
+                // The work autosaved beside it was replaced by the restored work.
+                if let Err(error) = remove_if_present(&aside(&path)) {
+                    println!("Could not delete {}: {}", aside(&path).display(), error);
+                }
+            }
+            Err(error) => self.status = Some(format!("Could not restore {}: {}", path.display(), error)),
+        }
     }
-}
-
-mod network{
-    use iced::{Color, Length, mouse, Point, Rectangle, Size, Vector};
-    use iced::keyboard::KeyCode::S;
-    use iced_graphics::canvas::{Cache, Cursor, Event, event, Fill, FillRule, Frame, Geometry, Path, Stroke};
-    use iced_native::event::Status;
 
-    pub struct Network{
-        nodes_cache: Cache,
-        interaction: Interaction,
-        translation: Vector,
-        scaling: f32,
-        nodes: Vec<Node>
+    /// Keeps the recovery file only if there is unsaved work to recover,
+    /// writing the latest changes to it right away since the app is about
+    /// to quit.
+    fn finish_recovery(&self){
+        let path = self.autosave_path();
+        let result = if self.network.is_dirty() {
+            std::fs::write(&path, self.network.export_json())
+        } else {
+            remove_if_present(&path)
+        };
+        if let Err(error) = result {
+            println!("Could not update {}: {}", path.display(), error);
+        }
     }
 
-    #[derive(Debug)]
-    pub enum NetworkMessage{
-        Update,
+    /// Writes the window geometry and the view, to restore on the next run.
+    fn save_session(&self){
+        let camera = self.network.camera();
+        let session = Session{
+            window_position: self.window_position,
+            window_size: self.window_size,
+            translation: (camera.translation.x, camera.translation.y),
+            scaling: camera.scaling,
+        };
+        if let Err(error) = session.save(Session::PATH) {
+            println!("Could not save the session: {}", error);
+        }
     }
 
-    enum Interaction{
-        None,
-        PanningScreen { translation: iced::Vector, start: iced::Point },
-        PanningNode { node_id: u32, translation: iced::Vector, start: iced::Point },
+    /// Sets how often animations such as panning inertia are advanced.
+    fn set_tick_interval(&mut self, interval: Duration){
+        self.tick_interval = interval;
     }
+}
 
-    impl Network{
-        const MIN_SCALING: f32 = 0.1;
-        const MAX_SCALING: f32 = 2.0;
+impl iced::Application for App{
+    type Executor = iced::executor::Default;
+    type Message = AppMessage;
+    type Flags = AppFlags;
 
-        pub fn new() -> Self{
-            let n1 = Node{
-                id: 0,
-                bounds: Rectangle{
-                    x: 0.,
-                    y: 0.,
-                    width: 100.,
-                    height: 100.,
-                },
-                color: Color::BLACK,
-                is_selected: false,
-            };
-            Network{
-                nodes_cache: Default::default(),
-                interaction: Interaction::None,
-                translation: Default::default(),
-                scaling: 1.0,
-                nodes: vec![n1]
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        let network = network::Network::new();
+        let mut app = Self{
+            palette: network.template_presets(),
+            network,
+            tick_interval: Self::DEFAULT_TICK_INTERVAL,
+            export_scale: flags.export_scale.filter(|scale| scale.is_finite() && *scale > 0.0).unwrap_or(1.0),
+            status: None,
+            live_url: flags.live_url,
+            live_status: ConnectionStatus::Connecting,
+            selected: 0,
+            properties: PropertiesPanel::new(),
+            tags: TagPanel::new(),
+            layers: LayerPanel::new(),
+            dragging_template: None,
+            window_position: flags.session.as_ref().and_then(|session| session.window_position),
+            window_size: flags.session.as_ref()
+                .map_or(window::Settings::default().size, |session| session.window_size),
+            exiting: false,
+            autosaved_at: Instant::now(),
+            autosaved_revision: 0,
+            pending_recovery: None,
+            restore_button: button::State::new(),
+            discard_button: button::State::new(),
+        };
+        let recovery = app.network.recovery_path();
+        // Work autosaved while the prompt was up last time is newer than
+        // the file it was waiting on.
+        if aside(&recovery).exists() {
+            if let Err(error) = std::fs::rename(aside(&recovery), &recovery) {
+                println!("Could not move {}: {}", aside(&recovery).display(), error);
             }
         }
-
-        pub fn view(&mut self) -> iced::Element<NetworkMessage> {
-            iced_graphics::Canvas::new(self)
-                .width(Length::Fill)
-                .height(Length::Fill)
-                .into()
+        if flags.recover {
+            // Restored the same way as from the prompt, keeping the file
+            // the recovery file belongs to.
+            app.pending_recovery = Some(recovery);
+            app.restore_recovery();
+        } else {
+            app.pending_recovery = app.network.unsaved_recovery();
         }
-
-        fn visible_region(&self, size: Size) -> Region {
-            let width = size.width / self.scaling;
-            let height = size.height / self.scaling;
-
-            Region {
-                x: -self.translation.x - width / 2.0,
-                y: -self.translation.y - height / 2.0,
-                width,
-                height,
-            }
+        if let Some(session) = &flags.session {
+            let (x, y) = session.translation;
+            app.network.set_camera(Camera{ translation: iced::Vector::new(x, y), scaling: session.scaling });
         }
-
-        fn project(&self, position: Point, size: Size) -> Point {
-            let region = self.visible_region(size);
-
-            Point::new(
-                position.x / self.scaling + region.x,
-                position.y / self.scaling + region.y,
-            )
-        }
-
-        fn get_node_at_screen(&mut self, position: Point) -> Option<u32>{
-            for node in self.nodes.iter_mut() {
-                if node.bounds.contains(position){
-                    return Some(node.id);
-                }
-            };
-            None
+        if let Some(interval) = flags.tick_interval {
+            app.set_tick_interval(interval);
         }
+        app.network.set_export_scale(app.export_scale);
+        let command = match flags.url {
+            Some(url) => app.update(AppMessage::LoadUrl(url)),
+            None => Command::none(),
+        };
+        (app, command)
+    }
 
-        fn unselect_all_nodes(&mut self){
-            for node in self.nodes.iter_mut(){
-                node.set_selected(false);
-            }
+    fn title(&self) -> String {
+        let marker = if self.network.is_dirty() { "*" } else { "" };
+        match self.network.current_path().and_then(|path| path.file_name()) {
+            Some(name) => format!("{}{} — Sword", name.to_string_lossy(), marker),
+            None if self.network.is_dirty() => format!("Untitled{} — Sword", marker),
+            None => String::from("Sword"),
         }
     }
 
-    impl<'a> iced_graphics::canvas::Program<NetworkMessage> for Network{
-        fn update(
-            &mut self,
-            event: Event,
-            bounds: Rectangle,
-            cursor: Cursor,
-        ) -> (event::Status, Option<NetworkMessage>) {
-
-            if let Event::Mouse(mouse::Event::ButtonReleased(_)) = event {
-                self.interaction = Interaction::None;
+    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        match message {
+            AppMessage::Tick(instant) => {
+                self.network.tick(instant);
+                if instant.duration_since(self.autosaved_at) >= Self::AUTOSAVE_INTERVAL {
+                    return self.autosave(instant);
+                }
             }
-
-            let cursor_position =
-                if let Some(position) = cursor.position_in(&bounds) {
-                    position
-                } else {
-                    return (event::Status::Ignored, None);
-                };
-
-            let node_id = self.get_node_at_screen(self.project(cursor_position, bounds.size()));
-
-            match event {
-                Event::Mouse(mouse_event) => match mouse_event {
-                    mouse::Event::ButtonPressed(button) => {
-                        let message = match button {
-                            mouse::Button::Left => {
-                                match node_id {
-                                    Some(id) => {
-                                        let node = self.nodes.iter_mut().find(|x| x.id == id);
-                                        match node{
-                                            Some(n) => {
-                                                self.interaction = Interaction::PanningNode {
-                                                    node_id: n.id,
-                                                    translation: Vector::new(n.bounds.x, n.bounds.y),
-                                                    start: cursor_position,
-                                                };
-                                                n.set_selected(true);
-                                            }
-                                            None => {
-                                                println!("Could not select node with id:{} because \
-                                                the node could not be found in the network", id);
-                                            }
-                                        }
-                                    }
-                                    None => {
-                                        self.unselect_all_nodes();
-                                    }
-                                }
-                                self.nodes_cache.clear();
-                                None
-                            }
-                            mouse::Button::Middle => {
-                                self.interaction = Interaction::PanningScreen {
-                                    translation: self.translation,
-                                    start: cursor_position,
-                                };
-                                None
-                            }
-                            _ => None,
-                        };
-                        (event::Status::Captured, message)
-                    }
-                    mouse::Event::CursorMoved { .. } => {
-                        let message = match self.interaction {
-                            Interaction::PanningScreen { translation, start } => {
-                                self.translation = translation
-                                    + (cursor_position - start)
-                                    * (1.0 / self.scaling);
-                                self.nodes_cache.clear();
-                                None
-                            }
-                            Interaction::PanningNode {node_id, translation, start } => {
-                                let node = self.nodes.iter_mut().find(|x| x.id == node_id);
-                                match node {
-                                    Some(n) => {
-                                        let new_pos = translation
-                                            + (cursor_position - start)
-                                            * (1.0 / self.scaling);
-                                        n.set_new_pos(new_pos);
-                                        self.nodes_cache.clear();
-                                    }
-                                    None => {
-                                        println!("Could not pan node with id:{} because \
-                                                the node could not be found in the network",
-                                                 node_id);
-                                    }
-                                }
-                                None
-                            }
-                            _ => None,
-                        };
-                        let event_status = match self.interaction {
-                            Interaction::None => event::Status::Ignored,
-                            _ => event::Status::Captured,
-                        };
-                        (event_status, message)
+            AppMessage::Network(NetworkMessage::SelectionChanged(ids)) => self.selected = ids.len(),
+            AppMessage::Network(NetworkMessage::Batch(messages)) => {
+                return Command::batch(messages.into_iter()
+                    .map(|message| self.update(AppMessage::Network(message))));
+            }
+            AppMessage::Network(_) => {}
+            AppMessage::Live(NetworkMessage::EdgeAdded{ id, from, to }) => match self.network.check_edge(from, to) {
+                Ok(()) => self.network.update_message(NetworkMessage::EdgeAdded{ id, from, to }),
+                Err(error) => self.status = Some(format!("Edge not added: {}", error)),
+            },
+            AppMessage::Live(message) => self.network.update_message(message),
+            AppMessage::LoadUrl(url) => {
+                self.status = Some(format!("Loading {}…", url));
+                return Command::perform(network::Network::fetch_json(url), |result| {
+                    AppMessage::GraphLoaded(result.map(Box::new))
+                });
+            }
+            AppMessage::GraphLoaded(Ok(network)) => {
+                self.network = *network;
+                self.network.set_export_scale(self.export_scale);
+                self.selected = 0;
+                self.status = None;
+                if self.pending_recovery.is_none() {
+                    self.pending_recovery = self.network.unsaved_recovery();
+                }
+            }
+            AppMessage::GraphLoaded(Err(error)) => {
+                self.status = Some(error.to_string());
+            }
+            AppMessage::LiveStatus(status) => self.live_status = status,
+            AppMessage::Window(WindowEvent::Moved{ x, y }) => self.window_position = Some((x, y)),
+            AppMessage::Window(WindowEvent::Resized{ width, height }) => self.window_size = (width, height),
+            AppMessage::Window(WindowEvent::CloseRequested) => {
+                self.save_session();
+                self.finish_recovery();
+                self.exiting = true;
+            }
+            AppMessage::Window(_) => {}
+            AppMessage::Properties(message) => self.properties.update(&mut self.network, message),
+            AppMessage::Tags(message) => self.tags.update(&mut self.network, message),
+            AppMessage::Layers(message) => self.layers.update(&mut self.network, message),
+            AppMessage::PaletteDrag(index) => {
+                self.dragging_template = index;
+                match index.and_then(|index| self.palette.get(index)) {
+                    Some(template) => self.network.start_template_drag(template.clone()),
+                    None => self.network.end_template_drag(),
+                }
+            }
+            AppMessage::Autosaved(Ok(())) => {}
+            AppMessage::Autosaved(Err(error)) => println!("Autosave failed: {}", error),
+            AppMessage::Recovery(RecoveryChoice::Restore) => self.restore_recovery(),
+            AppMessage::Recovery(RecoveryChoice::Discard) => {
+                if let Some(path) = self.pending_recovery.take() {
+                    match remove_if_present(&path) {
+                        Ok(()) => self.status = None,
+                        Err(error) => self.status = Some(format!("Could not delete {}: {}", path.display(), error)),
                     }
-                    mouse::Event::WheelScrolled { delta } => match delta {
-                        mouse::ScrollDelta::Lines { y, .. }
-                        | mouse::ScrollDelta::Pixels { y, .. } => {
-                            if y < 0.0 && self.scaling > Self::MIN_SCALING
-                                || y > 0.0 && self.scaling < Self::MAX_SCALING
-                            {
-                                let old_scaling = self.scaling;
-                                self.scaling = (self.scaling
-                                    * (1.0 + y / 30.0))
-                                    .max(Self::MIN_SCALING)
-                                    .min(Self::MAX_SCALING);
-
-                                if let Some(cursor_to_center) =
-                                cursor.position_from(bounds.center())
-                                {
-                                    let factor = self.scaling - old_scaling;
-                                    self.translation = self.translation
-                                        - Vector::new(
-                                        cursor_to_center.x * factor
-                                            / (old_scaling * old_scaling),
-                                        cursor_to_center.y * factor
-                                            / (old_scaling * old_scaling),
-                                    );
-                                }
-                                self.nodes_cache.clear();
-                            }
-                            (event::Status::Captured, None)
+                    // The work autosaved beside it takes its place.
+                    if aside(&path).exists() {
+                        if let Err(error) = std::fs::rename(aside(&path), &path) {
+                            self.status = Some(format!("Could not move {}: {}", aside(&path).display(), error));
                         }
-                    },
-                    _ => (event::Status::Ignored, None),
-                },
-                _ => (event::Status::Ignored, None),
+                    }
+                }
             }
-        }
-
-        fn draw(&self, bounds: Rectangle, cursor: Cursor) -> Vec<Geometry> {
-            let center = Vector::new(bounds.width / 2.0, bounds.height / 2.0);
-            let mut frame = Frame::new(bounds.size());
-            let background = Path::rectangle(Point::ORIGIN, frame.size());
-            frame.fill(&background, Color::from_rgb8(0x04, 0x44, 0x48));
-
-            let nodes = self.nodes_cache.draw(bounds.size(), |frame| {
-                for node in &self.nodes{
-                    frame.translate(center);
-                    frame.scale(self.scaling);
-                    frame.translate(self.translation);
-
-                    let line_stroke = Stroke{
-                        color: Color::WHITE,
-                        width: 5.0 * self.scaling,
-                        ..Stroke::default()
-                    };
-                    let line = Path::line(Point::new(0., 0.), Point::new(500.0, 0.0));
-                    frame.fill(&line, Color::WHITE);
-                    frame.stroke(&line, line_stroke);
-
-                    node.draw(frame, self.scaling);
+            AppMessage::Open => return Command::perform(dialog::open_file(), AppMessage::OpenPicked),
+            AppMessage::Save{ choose } => match self.network.current_path() {
+                Some(path) if !choose => return self.save_to(path.to_path_buf()),
+                current => {
+                    let current = current.map(|path| path.to_path_buf());
+                    return Command::perform(dialog::save_file(current), AppMessage::SavePicked);
                 }
-            });
-
-            vec![frame.into_geometry(), nodes]
+            },
+            AppMessage::OpenPicked(Ok(Some(path))) => {
+                self.status = Some(format!("Opening {}…", path.display()));
+                return Command::perform(network::Network::read_json(path), |result| {
+                    AppMessage::GraphLoaded(result.map(Box::new))
+                });
+            }
+            AppMessage::Import => return Command::perform(dialog::open_file(), AppMessage::ImportPicked),
+            AppMessage::ImportPicked(Ok(Some(path))) => {
+                self.status = Some(format!("Importing {}…", path.display()));
+                return Command::perform(network::Network::read_json(path), |result| {
+                    AppMessage::GraphImported(result.map(Box::new))
+                });
+            }
+            AppMessage::GraphImported(Ok(graph)) => {
+                let merged = self.network.merge(*graph);
+                self.selected = merged.len();
+                self.status = Some(format!("Imported {} nodes", merged.len()));
+            }
+            AppMessage::GraphImported(Err(error)) => self.status = Some(error.to_string()),
+            AppMessage::SavePicked(Ok(Some(path))) => return self.save_to(path),
+            AppMessage::ExportSelection => {
+                if self.network.selected_node_count() == 0 {
+                    self.status = Some(String::from("Select the nodes to export first"));
+                } else {
+                    return Command::perform(dialog::save_file(None), AppMessage::ExportPicked);
+                }
+            }
+            AppMessage::ExportPicked(Ok(Some(path))) => {
+                let ids: HashSet<u32> = self.network.selected_node_ids().into_iter().collect();
+                let graph = SerNetwork::from(&self.network.subgraph(&ids));
+                self.status = Some(format!("Exporting {} nodes to {}…", ids.len(), path.display()));
+                return Command::perform(network::Network::write_json(graph, path), AppMessage::Exported);
+            }
+            AppMessage::Exported(Ok(())) => self.status = Some(String::from("Selection exported")),
+            AppMessage::Exported(Err(error)) => self.status = Some(format!("Export failed: {}", error)),
+            AppMessage::OpenPicked(Ok(None))
+            | AppMessage::ImportPicked(Ok(None))
+            | AppMessage::SavePicked(Ok(None))
+            | AppMessage::ExportPicked(Ok(None)) => {}
+            AppMessage::OpenPicked(Err(error))
+            | AppMessage::ImportPicked(Err(error))
+            | AppMessage::SavePicked(Err(error))
+            | AppMessage::ExportPicked(Err(error)) => self.status = Some(error),
+            AppMessage::Saved{ path, revision, result: Ok(()) } => {
+                self.status = Some(format!("Saved {}", path.display()));
+                self.network.mark_saved(path, revision);
+            }
+            AppMessage::Saved{ result: Err(error), .. } => self.status = Some(format!("Save failed: {}", error)),
         }
+        Command::none()
     }
 
-    pub struct Region {
-        x: f32,
-        y: f32,
-        width: f32,
-        height: f32,
+    fn subscription(&self) -> Subscription<Self::Message> {
+        // Nothing to advance while idle, so only wake up for autosaves.
+        let interval = if self.network.is_animating() {
+            self.tick_interval
+        } else {
+            Self::AUTOSAVE_INTERVAL
+        };
+        let ticks = time::every(interval).map(AppMessage::Tick);
+        let updates = match &self.live_url {
+            Some(url) => live::connect(url.clone()).map(|event| match event {
+                LiveEvent::Status(status) => AppMessage::LiveStatus(status),
+                LiveEvent::Message(message) => AppMessage::Live(message),
+            }),
+            None => Subscription::none(),
+        };
+        let window = subscription::events_with(|event, _| match event {
+            Event::Window(event @ (WindowEvent::Moved{ .. }
+                | WindowEvent::Resized{ .. }
+                | WindowEvent::CloseRequested)) => Some(AppMessage::Window(event)),
+            Event::Keyboard(keyboard::Event::KeyPressed{ key_code, modifiers }) if modifiers.control() => match key_code {
+                keyboard::KeyCode::O if modifiers.shift() => Some(AppMessage::Import),
+                keyboard::KeyCode::O => Some(AppMessage::Open),
+                keyboard::KeyCode::E if modifiers.shift() => Some(AppMessage::ExportSelection),
+                keyboard::KeyCode::S => Some(AppMessage::Save{ choose: modifiers.shift() }),
+                _ => None,
+            },
+            _ => None,
+        });
+        Subscription::batch([ticks, updates, window])
     }
 
-    struct Node{
-        id: u32,
-        bounds: Rectangle,
-        color: Color,
-        is_selected: bool,
+    fn should_exit(&self) -> bool {
+        self.exiting
     }
 
-    impl Node {
-        fn draw(&self, frame: &mut Frame, scale: f32) {
-            let position = Point::new(self.bounds.x, self.bounds.y);
-            let body = Path::rectangle(position, self.bounds.size());
-            let normal_stroke = Stroke{
-                color: self.color,
-                width: 2.5 * scale,
-                ..Stroke::default()
-            };
-            let selected_stroke = Stroke{
-                color: Color::from_rgb(1., 0., 0.),
-                width: 2.5 * scale,
-                ..Stroke::default()
+    fn view(&mut self) -> Element<'_, Self::Message> {
+        let side = Column::new()
+            .push(self.properties.view(&self.network).map(AppMessage::Properties))
+            .push(self.tags.view(&self.network).map(AppMessage::Tags))
+            .push(self.layers.view(&self.network).map(AppMessage::Layers));
+        let palette = Panel::new(&self.palette, self.dragging_template, self.network.theme().node, AppMessage::PaletteDrag);
+        let mut content = Column::new();
+        if let Some(path) = &self.pending_recovery {
+            let prompt: Element<'_, RecoveryChoice> = Row::new()
+                .padding(6)
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(Text::new(format!("Unsaved work from a session that didn't exit cleanly is in {}.", path.display()))
+                    .size(16)
+                    .width(Length::Fill))
+                .push(Button::new(&mut self.restore_button, Text::new("Restore").size(16))
+                    .on_press(RecoveryChoice::Restore))
+                .push(Button::new(&mut self.discard_button, Text::new("Discard").size(16))
+                    .on_press(RecoveryChoice::Discard))
+                .into();
+            content = content.push(prompt.map(AppMessage::Recovery));
+        }
+        content = content.push(self.network
+            .view()
+            .map(AppMessage::Network)
+        );
+        if let Some(url) = &self.live_url {
+            let connection = match &self.live_status {
+                ConnectionStatus::Connecting => String::from("connecting…"),
+                ConnectionStatus::Connected => String::from("connected"),
+                ConnectionStatus::Disconnected{ retry_in } => {
+                    format!("disconnected, retrying in {}s", retry_in.as_secs())
+                }
             };
-            frame.fill(&body, self.color);
-            if self.is_selected{
-                frame.stroke(&body, selected_stroke);
-            }else{
-                frame.stroke(&body, normal_stroke);
-            }
+            content = content.push(Text::new(format!("{}: {}", url, connection)).size(16));
         }
-
-        fn set_selected(&mut self, selected: bool){
-            self.is_selected = selected;
+        if self.selected > 0 {
+            content = content.push(Text::new(format!("{} selected", self.selected)).size(16));
         }
-
-        fn set_new_pos(&mut self, new_pos: Vector){
-            self.bounds.x = new_pos.x;
-            self.bounds.y = new_pos.y;
-        }
-
-        fn get_pos(&self) -> Vector{
-            Vector::new(self.bounds.x, self.bounds.y)
+        if let Some(status) = &self.status {
+            content = content.push(Text::new(status.as_str()).size(16));
         }
+        let content = Row::new()
+            .push(palette)
+            .push(content.width(Length::Fill))
+            .push(side);
+        Container::new(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
     }
 }
 
-struct Panel{
-    
-}
 
-impl<Message, Renderer> Widget<Message, Renderer> for Panel
-    where Renderer: renderer::Renderer,
-{
-    fn width(&self) -> Length {
-        todo!()
-    }
-
-    fn height(&self) -> Length {
-        todo!()
-    }
+/// The file autosaves go to while the recovery file at `path` is waiting
+/// to be restored or discarded.
+fn aside(path: &Path) -> PathBuf{
+    let mut name = path.as_os_str().to_owned();
+    name.push(".new");
+    PathBuf::from(name)
+}
 
-    fn layout(&self, renderer: &Renderer, limits: &Limits) -> Node {
-        todo!()
+/// Deletes the file at `path`, if there is one.
+fn remove_if_present(path: &Path) -> std::io::Result<()>{
+    match std::fs::remove_file(path) {
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
     }
+}
 
-    fn draw(&self, renderer: &mut Renderer, style: &Style, layout: Layout<'_>, cursor_position: Point, viewport: &Rectangle) {
-
+#[cfg(test)]
+mod tests{
+    use iced::{mouse, Application, Point, Rectangle};
+    use iced::canvas::{Cursor, Event, Program};
+
+    use super::{App, AppFlags, AppMessage};
+
+    #[test]
+    fn dropping_a_selection_changes_the_revision_at_most_once(){
+        let (mut app, _) = App::new(AppFlags::default());
+        app.network.select_all();
+        let bounds = Rectangle{ x: 0.0, y: 0.0, width: 800.0, height: 600.0 };
+        let send = |app: &mut App, event: mouse::Event, position: Point| {
+            app.network.update(Event::Mouse(event), bounds, Cursor::Available(position)).1
+        };
+        // The default view puts the first node's corner at the canvas center.
+        send(&mut app, mouse::Event::ButtonPressed(mouse::Button::Left), Point::new(410.0, 310.0));
+        send(&mut app, mouse::Event::CursorMoved{ position: Point::new(470.0, 350.0) }, Point::new(470.0, 350.0));
+        let revision = app.network.revision();
+        let message = send(&mut app, mouse::Event::ButtonReleased(mouse::Button::Left), Point::new(470.0, 350.0))
+            .expect("the drop reports the moved nodes");
+        let _ = app.update(AppMessage::Network(message));
+        assert!(app.network.revision() <= revision + 1, "{} after {}", app.network.revision(), revision);
     }
-}
\ No newline at end of file
+}
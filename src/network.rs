@@ -0,0 +1,2427 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::{Path as FilePath, PathBuf};
+use std::time::Instant;
+
+use iced::{alignment, Color, keyboard, Length, mouse, Point, Rectangle, Size, Vector};
+use iced::keyboard::KeyCode;
+use iced_graphics::canvas::{Cache, Cursor, Event, event, Frame, Geometry, LineDash, Path, Stroke, Text};
+use image::RgbaImage;
+use serde::Deserialize;
+use animation::{CameraAnimation, LayoutAnimation};
+use annotation::{Annotation, TextAnnotation};
+use cursor::RemoteCursor;
+use edge::{Bend, Edge, EdgeRoute};
+use edit::{EditTarget, LabelEdit};
+use geometry::{DrawKey, GeometryCache, Scene};
+use graph::Highlight;
+use group::Group;
+use inertia::PanSample;
+use interaction::Interaction;
+use raster::Raster;
+use snap::Guide;
+use stats::FrameStats;
+
+mod animation;
+mod annotation;
+mod builder;
+mod camera;
+mod csv;
+mod cursor;
+mod dot;
+mod edge;
+mod edit;
+mod geometry;
+mod graph;
+mod graphml;
+mod group;
+mod icon;
+mod inertia;
+mod interaction;
+mod json;
+mod layer;
+mod layout;
+mod measure;
+mod merge;
+mod raster;
+mod reconnect;
+mod ruler;
+mod search;
+mod snap;
+mod serde_model;
+mod shape;
+mod stats;
+mod tags;
+mod template;
+mod theme;
+
+pub use builder::NetworkBuilder;
+pub use camera::Camera;
+pub use dot::ParseError;
+pub use edge::{EdgeError, EdgeStyle, LineStyle};
+pub use icon::Icon;
+pub use json::LoadError;
+pub use layer::Layer;
+pub use layout::AlignKind;
+pub use reconnect::EdgeEnd;
+pub use serde_model::{SerAnnotation, SerColor, SerEdge, SerGroup, SerNetwork, SerNode, SerPort, SerText};
+pub use shape::NodeShape;
+pub use template::NodeTemplate;
+pub use theme::Theme;
+
+pub struct Network{
+    /// Group boxes. Nodes and edges each cache their own geometry.
+    groups_cache: Cache,
+    /// Key of the view in the last frame drawn, to tell whether it moved;
+    /// see `Scene`.
+    last_view: Cell<Option<u64>>,
+    interaction: Interaction,
+    /// Latest cursor move of a drag, not handled yet; see
+    /// `defer_cursor_move`.
+    pending_move: Option<(mouse::Event, Rectangle, Cursor)>,
+    translation: Vector,
+    scaling: f32,
+    min_scaling: f32,
+    max_scaling: f32,
+    lod_threshold: f32,
+    /// Pixels per canvas pixel in the Ctrl+P export.
+    export_scale: f32,
+    /// Size and fill of nodes created without one of their own; a `None`
+    /// fill follows the theme.
+    default_node_size: Size,
+    default_node_color: Option<Color>,
+    /// World area the center of the view is kept in, if any.
+    pan_bounds: Option<Rectangle>,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    groups: Vec<Group>,
+    annotations: Vec<Annotation>,
+    texts: Vec<TextAnnotation>,
+    layers: Vec<Layer>,
+    remote_cursors: Vec<RemoteCursor>,
+    edge_style: EdgeStyle,
+    theme: Theme,
+    highlight: Option<Highlight>,
+    /// Fill of each node by connected component, drawn instead of the
+    /// node colors while `toggle_component_colors` is on.
+    component_colors: Option<HashMap<u32, Color>>,
+    /// Node sizes from before `size_by_degree`, while it is on.
+    original_sizes: Option<HashMap<u32, Size>>,
+    next_id: u32,
+    current_path: Option<PathBuf>,
+    dirty: bool,
+    /// Bumped by every change, see `revision`.
+    revision: u64,
+    searching: bool,
+    search_query: String,
+    search_index: usize,
+    label_edit: Option<LabelEdit>,
+    tool: Tool,
+    /// Node last reached with Tab, see `select_next_node`.
+    tab_focus: Option<u32>,
+    /// Palette template being dragged over the window, if any.
+    template_drag: Option<NodeTemplate>,
+    /// Tag a node needs to be drawn normally; the others are dimmed.
+    active_tag_filter: Option<String>,
+    /// World end points of the last finished measurement.
+    measurement: Option<(Point, Point)>,
+    /// Alignment guides of the node being dragged.
+    guides: Vec<Guide>,
+    animate_layouts: bool,
+    layout_animation: Option<LayoutAnimation>,
+    camera_animation: Option<CameraAnimation>,
+    inertia_enabled: bool,
+    pan_velocity: Vector,
+    pan_sample: Option<PanSample>,
+    last_tick: Option<Instant>,
+    modifiers: keyboard::Modifiers,
+    /// Whether key presses are meant for the canvas: set by clicking it,
+    /// cleared by clicking anywhere else, e.g. a text field next to it.
+    focused: bool,
+    /// Mouse button that drags the view.
+    pan_button: mouse::Button,
+    /// Whether Space is held down, which turns left drags into pans.
+    space_held: bool,
+    /// Whether the cursor has left the drag threshold since the button was
+    /// pressed.
+    drag_started: bool,
+    show_frame_stats: bool,
+    show_rulers: bool,
+    show_axes: bool,
+    frame_stats: FrameStats,
+    on_view_changed: Option<Box<dyn FnMut(Vector, f32) + Send>>,
+}
+
+/// Messages produced by the canvas, and changes the host can apply with
+/// `Network::update_message`. The change variants deserialize from JSON
+/// objects tagged with their snake_case name, e.g.
+/// `{"type": "edge_added", "id": 7, "from": 1, "to": 2}`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NetworkMessage{
+    /// The network changed in a way the host may want to react to.
+    #[serde(skip)]
+    Update,
+    /// Ids of the nodes selected after a click or key press changed the
+    /// selection.
+    #[serde(skip)]
+    SelectionChanged(Vec<u32>),
+    /// Adds a node with its top-left corner at `x`, `y`. Ignored if `id` is
+    /// already used; `NodeMoved` and `NodeUpdated` change existing nodes.
+    NodeAdded{
+        id: u32,
+        #[serde(default)]
+        label: String,
+        x: f32,
+        y: f32,
+    },
+    /// Moves a node's top-left corner to `x`, `y`. Also sent by the canvas
+    /// for every node that moved when the user lets go of a dragged node,
+    /// after moving them.
+    NodeMoved{ id: u32, x: f32, y: f32 },
+    NodeUpdated{ id: u32, label: String },
+    /// Removes a node along with its edges.
+    NodeRemoved{ id: u32 },
+    EdgeAdded{ id: u32, from: u32, to: u32 },
+    EdgeRemoved{ id: u32 },
+    /// Moves another user's cursor to the world position `x`, `y`; see
+    /// `Network::set_remote_cursor`.
+    CursorMoved{ user: String, x: f32, y: f32 },
+    /// Several of the above from a single event, in order.
+    #[serde(skip)]
+    Batch(Vec<NetworkMessage>),
+}
+
+/// What clicks and drags on the canvas do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tool{
+    /// Select, move and pan.
+    #[default]
+    Select,
+    /// Two clicks measure the distance between them.
+    Measure,
+    /// Dragging draws a freehand annotation.
+    Pen,
+    /// A click places a text note, or edits the one under the cursor.
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis{
+    Horizontal,
+    Vertical,
+}
+
+impl Network{
+    const DEFAULT_MIN_SCALING: f32 = 0.1;
+    const DEFAULT_MAX_SCALING: f32 = 2.0;
+    const DEFAULT_LOD_THRESHOLD: f32 = 0.3;
+    const GRID_SIZE: f32 = 10.0;
+    const LARGE_NUDGE_STEPS: f32 = 5.0;
+    const EDGE_HIT_DISTANCE: f32 = 6.0;
+    const DEFAULT_NODE_SIZE: f32 = 100.0;
+    /// Rotation of the selection per press of `[` or `]`, in degrees.
+    const ROTATION_STEP: f32 = 15.0;
+    /// Screen distance panned per wheel line when scrolling sideways.
+    const SCROLL_LINE_HEIGHT: f32 = 40.0;
+    const EXPORT_PATH: &'static str = "sword.png";
+    const GRAPHML_PATH: &'static str = "sword.graphml";
+    const ADJACENCY_PATH: &'static str = "sword.csv";
+    /// Where untitled networks are autosaved.
+    const RECOVERY_PATH: &'static str = "sword.recovery";
+    const FORCE_LAYOUT_ITERATIONS: usize = 200;
+    /// Gap between grid cells used by the grid layout key.
+    const GRID_LAYOUT_SPACING: f32 = 40.0;
+    /// Gap between the selected nodes and the selection outline.
+    const SELECTION_MARGIN: f32 = 6.0;
+    /// Screen distance the cursor must travel before a press turns into a
+    /// drag, so a slightly shaky click stays a click.
+    const DRAG_THRESHOLD: f32 = 4.0;
+    /// Screen distance outside the view within which things are still drawn,
+    /// so strokes, arrowheads and labels sticking out of their bounds don't
+    /// pop in at the border.
+    const CULL_MARGIN: f32 = 30.0;
+
+    pub fn new() -> Self{
+        let mut builder = NetworkBuilder::new();
+        let n1 = builder.add_node("", Point::new(0., 0.), None);
+        let n2 = builder.add_node("", Point::new(400., 0.), None);
+        builder.add_edge(n1, n2).expect("the two nodes are distinct");
+        builder.build()
+    }
+
+    /// A network without nodes or edges. Neither this nor any other
+    /// constructor needs a window: the node cache stays empty until the
+    /// canvas first draws, so the model can be driven headless.
+    pub fn empty() -> Self{
+        Network{
+            groups_cache: Default::default(),
+            last_view: Cell::new(None),
+            interaction: Interaction::None,
+            pending_move: None,
+            translation: Default::default(),
+            scaling: 1.0,
+            min_scaling: Self::DEFAULT_MIN_SCALING,
+            max_scaling: Self::DEFAULT_MAX_SCALING,
+            lod_threshold: Self::DEFAULT_LOD_THRESHOLD,
+            export_scale: 1.0,
+            default_node_size: Size::new(Self::DEFAULT_NODE_SIZE, Self::DEFAULT_NODE_SIZE),
+            default_node_color: None,
+            pan_bounds: None,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            groups: Vec::new(),
+            annotations: Vec::new(),
+            texts: Vec::new(),
+            layers: vec![Layer::new(Layer::DEFAULT)],
+            remote_cursors: Vec::new(),
+            edge_style: EdgeStyle::default(),
+            theme: Theme::default(),
+            highlight: None,
+            component_colors: None,
+            original_sizes: None,
+            next_id: 0,
+            current_path: None,
+            dirty: false,
+            revision: 0,
+            searching: false,
+            search_query: String::new(),
+            search_index: 0,
+            label_edit: None,
+            tool: Tool::default(),
+            tab_focus: None,
+            template_drag: None,
+            active_tag_filter: None,
+            measurement: None,
+            guides: Vec::new(),
+            animate_layouts: true,
+            layout_animation: None,
+            camera_animation: None,
+            inertia_enabled: true,
+            pan_velocity: Vector::new(0., 0.),
+            pan_sample: None,
+            last_tick: None,
+            modifiers: keyboard::Modifiers::default(),
+            focused: true,
+            pan_button: mouse::Button::Middle,
+            space_held: false,
+            drag_started: false,
+            show_frame_stats: false,
+            show_rulers: false,
+            show_axes: false,
+            frame_stats: FrameStats::default(),
+            on_view_changed: None,
+        }
+    }
+
+    /// Builds a network from a Graphviz DOT document such as
+    /// `digraph { a -> b; b -> c; }`. Every identifier becomes a node labelled
+    /// with it, laid out on a circle since DOT carries no positions.
+    /// Attributes are ignored, and so are self-loops and repeated edges,
+    /// which `check_edge` refuses.
+    pub fn import_dot(src: &str) -> Result<Network, ParseError>{
+        let graph = dot::parse(src)?;
+        let mut network = Network::empty();
+        let count = graph.nodes.len();
+        let radius = (count as f32 * Self::DEFAULT_NODE_SIZE * 1.5 / (2.0 * std::f32::consts::PI))
+            .max(Self::DEFAULT_NODE_SIZE * 2.0);
+        let mut ids = Vec::with_capacity(count);
+        for (i, label) in graph.nodes.into_iter().enumerate() {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / count as f32;
+            let id = network.allocate_id();
+            let mut node = Node::new(id, Rectangle{
+                x: radius * angle.cos() - Self::DEFAULT_NODE_SIZE / 2.0,
+                y: radius * angle.sin() - Self::DEFAULT_NODE_SIZE / 2.0,
+                width: Self::DEFAULT_NODE_SIZE,
+                height: Self::DEFAULT_NODE_SIZE,
+            });
+            node.label = label;
+            network.nodes.push(node);
+            ids.push(id);
+        }
+        for (from, to) in graph.edges {
+            let (from, to) = (ids[from], ids[to]);
+            if network.check_edge(from, to).is_ok() {
+                let id = network.allocate_id();
+                network.edges.push(Edge::new(id, from, to));
+            }
+        }
+        network.fit_edge_widths();
+        network.fit_parallel_edges();
+        Ok(network)
+    }
+
+    /// Sets how far the view can be zoomed out (`min`) and in (`max`). A
+    /// `min` that isn't a positive number falls back to the default, as
+    /// does a `max` that isn't finite, and a `max` below `min` is raised
+    /// to it.
+    pub fn with_scaling_limits(mut self, min: f32, max: f32) -> Self{
+        let min = if min.is_finite() && min > 0.0 { min } else { Self::DEFAULT_MIN_SCALING };
+        let max = if max.is_finite() { max } else { Self::DEFAULT_MAX_SCALING }.max(min);
+        self.min_scaling = min;
+        self.max_scaling = max;
+        self.scaling = self.scaling.clamp(min, max);
+        self.request_redraw();
+        self
+    }
+
+    /// Sets the mouse button that drags the view, the middle one by
+    /// default; e.g. the right one for mice without a middle button. The
+    /// pan button takes precedence over anything else the button does, so
+    /// the left one would leave no way to select.
+    pub fn set_pan_button(&mut self, button: mouse::Button){
+        self.pan_button = button;
+    }
+
+    /// Below this scaling, nodes are drawn as plain dots and labels are left
+    /// out, which keeps zoomed-out overviews fast and readable.
+    pub fn set_lod_threshold(&mut self, threshold: f32){
+        self.lod_threshold = threshold;
+        self.request_redraw();
+    }
+
+    /// Sets the resolution of the Ctrl+P export as a multiple of the
+    /// canvas size, e.g. 2 for an image twice as wide and high. Scales
+    /// that aren't positive and finite are ignored.
+    pub fn set_export_scale(&mut self, scale: f32){
+        if scale.is_finite() && scale > 0.0 {
+            self.export_scale = scale;
+        }
+    }
+
+    /// Size of nodes created without one, e.g. by a live update or the
+    /// builder, and of the preset templates. Existing nodes keep theirs.
+    pub fn set_default_node_size(&mut self, size: Size){
+        self.default_node_size = size;
+    }
+
+    /// Fill of nodes created without one, e.g. from a template without a
+    /// color. `None` uses the theme's node color. Existing nodes keep
+    /// theirs.
+    pub fn set_default_node_color(&mut self, color: Option<Color>){
+        self.default_node_color = color;
+    }
+
+    /// File the network was last loaded from or saved to, if any.
+    pub fn current_path(&self) -> Option<&FilePath>{
+        self.current_path.as_deref()
+    }
+
+    /// Whether nodes, edges or the view changed since the last save.
+    pub fn is_dirty(&self) -> bool{
+        self.dirty
+    }
+
+    /// Counts the changes made to the network. Two different readings mean
+    /// something changed in between, e.g. since the last autosave.
+    pub fn revision(&self) -> u64{
+        self.revision
+    }
+
+    /// File autosaves go to: next to the current file with `.recovery`
+    /// appended, or `sword.recovery` for an untitled network.
+    pub fn recovery_path(&self) -> PathBuf{
+        match &self.current_path {
+            Some(path) => {
+                let mut name = path.clone().into_os_string();
+                name.push(".recovery");
+                PathBuf::from(name)
+            }
+            None => PathBuf::from(Self::RECOVERY_PATH),
+        }
+    }
+
+    /// Records that the network as it was at `revision` was saved to
+    /// `path`. The dirty flag is cleared unless it changed since.
+    pub fn mark_saved(&mut self, path: PathBuf, revision: u64){
+        self.current_path = Some(path);
+        self.dirty = self.revision != revision;
+    }
+
+    /// Flags the document as modified, refits the group boxes around their
+    /// members and redraws the nodes.
+    fn mark_dirty(&mut self){
+        self.dirty = true;
+        self.revision += 1;
+        self.fit_groups();
+        self.fit_edge_widths();
+        self.fit_parallel_edges();
+        if self.component_colors.is_some() {
+            self.component_colors = Some(self.component_palette());
+        }
+        self.request_redraw();
+    }
+
+    /// Records that the scene changed, so the group boxes are drawn again.
+    /// Nodes and edges notice their own changes through their draw keys.
+    /// The cache is cleared right away rather than before the next `view`,
+    /// since events that send no message are drawn without one.
+    fn request_redraw(&mut self){
+        self.groups_cache.clear();
+    }
+
+    /// Hands out an id that is not used by any item in the network.
+    /// Every node and edge creation path must go through here.
+    fn allocate_id(&mut self) -> u32{
+        let id = self.next_id;
+        match id.checked_add(1) {
+            Some(next) => self.next_id = next,
+            // The counter ran out; fall back on the lowest free id.
+            None => return (0..u32::MAX).find(|id| !self.id_in_use(*id)).expect("every id is in use"),
+        }
+        id
+    }
+
+    /// Whether a node, edge, group or text uses `id`. They share one id
+    /// space.
+    fn id_in_use(&self, id: u32) -> bool{
+        self.nodes.iter().any(|node| node.id == id)
+            || self.edges.iter().any(|edge| edge.id == id)
+            || self.groups.iter().any(|group| group.id == id)
+            || self.texts.iter().any(|text| text.id == id)
+    }
+
+    /// Takes an id chosen elsewhere, e.g. by a peer, and moves the counter
+    /// past it. Refuses ids already in use and `u32::MAX`, past which the
+    /// counter can't move.
+    fn claim_id(&mut self, id: u32) -> bool{
+        match id.checked_add(1) {
+            Some(next) if !self.id_in_use(id) => {
+                self.next_id = self.next_id.max(next);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Registers a callback invoked with the new translation and scaling
+    /// whenever an event moves the camera. Useful to keep linked views in sync.
+    pub fn set_on_view_changed(&mut self, callback: impl FnMut(Vector, f32) + Send + 'static){
+        self.on_view_changed = Some(Box::new(callback));
+    }
+
+    /// Advances time-based effects such as panning inertia. Meant to be
+    /// driven by the host's tick subscription.
+    pub fn tick(&mut self, now: Instant){
+        let elapsed = self.last_tick.map(|last| now.saturating_duration_since(last));
+        self.last_tick = Some(now);
+        let elapsed = match elapsed {
+            Some(elapsed) => elapsed,
+            None => return,
+        };
+        if self.tick_inertia(elapsed) {
+            self.request_redraw();
+            self.notify_view_changed();
+        }
+        self.tick_layout_animation(elapsed);
+        self.tick_remote_cursors(elapsed);
+        if self.tick_camera_animation(elapsed) {
+            self.notify_view_changed();
+        }
+        if !self.is_animating() {
+            // Ticks may stop until the next animation; don't count the pause
+            // as elapsed time when they resume.
+            self.last_tick = None;
+        }
+    }
+
+    /// Whether something is moving on its own and needs `tick` to be called.
+    /// Hosts can stop ticking while this is false. Remote cursors count,
+    /// since they fade out over time.
+    pub fn is_animating(&self) -> bool{
+        self.inertia_active() || self.layout_animating() || self.camera_animation.is_some()
+            || !self.remote_cursors.is_empty()
+    }
+
+    fn notify_view_changed(&mut self){
+        if let Some(callback) = self.on_view_changed.as_mut() {
+            callback(self.translation, self.scaling);
+        }
+    }
+
+    pub fn view(&mut self) -> iced::Element<'_, NetworkMessage> {
+        self.flush_cursor_move();
+        self.expire_highlight();
+        iced_graphics::Canvas::new(self)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    /// The current view transform, detached from the network.
+    pub fn camera(&self) -> Camera{
+        Camera{ translation: self.translation, scaling: self.scaling }
+    }
+
+    /// Moves the view to `camera`, with the scaling clamped to the zoom
+    /// limits.
+    pub fn set_camera(&mut self, camera: Camera){
+        self.translation = camera.translation;
+        self.scaling = camera.scaling.clamp(self.min_scaling, self.max_scaling);
+        self.clamp_translation();
+        self.request_redraw();
+    }
+
+    /// Translation and scaling of the view, e.g. to save it or to show the
+    /// same area in a linked canvas. Same as `camera`, unpacked.
+    pub fn view_state(&self) -> (Vector, f32){
+        (self.translation, self.scaling)
+    }
+
+    /// Moves the view, with the scaling clamped to the zoom limits; see
+    /// `view_state`.
+    pub fn set_view_state(&mut self, translation: Vector, scaling: f32){
+        self.set_camera(Camera{ translation, scaling });
+    }
+
+    /// World area shown in a canvas of `size`.
+    pub fn visible_region(&self, size: Size) -> Region {
+        self.camera().visible_region(size)
+    }
+
+    /// Maps a position in a canvas of `size` to world space; see
+    /// `Camera::screen_to_world`.
+    pub fn screen_to_world(&self, position: Point, size: Size) -> Point {
+        self.camera().screen_to_world(position, size)
+    }
+
+    /// Maps a world position to where it is drawn in a canvas of `size`.
+    pub fn world_to_screen(&self, world: Point, size: Size) -> Point {
+        self.camera().world_to_screen(world, size)
+    }
+
+    /// Nodes that need drawing in world space in a view of the given size:
+    /// the ones that aren't pinned, hidden or outside the visible region.
+    fn drawn_nodes(&self, size: Size) -> impl Iterator<Item = &Node>{
+        self.nodes.iter()
+            .filter(move |node| !node.pinned && self.is_in_view(node.bounds, size) && !self.is_hidden(node))
+    }
+
+    /// Whether world `bounds` are in or near the view of a canvas of
+    /// `size`, and so need drawing. Everything else is culled.
+    fn is_in_view(&self, bounds: Rectangle, size: Size) -> bool{
+        self.visible_region(size).intersects(bounds, Self::CULL_MARGIN / self.scaling)
+    }
+
+    /// Returns the id of the node under a position in a canvas of `size`.
+    /// Pinned nodes are drawn over the others, so they are hit-tested
+    /// first, in screen space.
+    fn get_node_at_screen(&self, position: Point, size: Size) -> Option<u32>{
+        self.nodes.iter()
+            .rev()
+            .find(|node| node.pinned && node.contains(position) && self.is_clickable(node))
+            .map(|node| node.id)
+            .or_else(|| self.topmost_node_at(self.screen_to_world(position, size)))
+    }
+
+    /// Returns the id of the node that would receive a click at the given
+    /// world position. Nodes are drawn in order, so the last one containing
+    /// the point is the one on top. Pinned nodes, nodes hidden in a
+    /// collapsed group and nodes on a hidden or locked layer are skipped.
+    pub fn topmost_node_at(&self, world: Point) -> Option<u32>{
+        self.nodes.iter()
+            .rev()
+            .find(|node| !node.pinned && node.contains(world) && self.is_clickable(node))
+            .map(|node| node.id)
+    }
+
+    fn is_clickable(&self, node: &Node) -> bool{
+        !self.is_hidden(node) && self.is_layer_available(&node.layer)
+    }
+
+    fn unselect_all_nodes(&mut self){
+        for node in self.nodes.iter_mut(){
+            if node.is_selected {
+                node.set_selected(false);
+                self.groups_cache.clear();
+            }
+        }
+    }
+
+    fn unselect_all_edges(&mut self){
+        for edge in self.edges.iter_mut(){
+            if edge.is_selected {
+                edge.is_selected = false;
+                self.groups_cache.clear();
+            }
+        }
+    }
+
+    /// Selects every node and edge. Nodes hidden in a collapsed group and
+    /// items on hidden or locked layers are left alone.
+    pub fn select_all(&mut self){
+        for node in self.nodes.iter_mut(){
+            let hidden = self.groups.iter()
+                .any(|group| group.collapsed && group.member_ids.contains(&node.id));
+            if !node.is_selected && !hidden {
+                node.set_selected(true);
+                self.groups_cache.clear();
+            }
+        }
+        for edge in self.edges.iter_mut(){
+            if !edge.is_selected {
+                edge.is_selected = true;
+                self.groups_cache.clear();
+            }
+        }
+        self.unselect_unavailable();
+    }
+
+    /// Selects the nodes that are not selected and unselects the others.
+    /// Nodes hidden in a collapsed group and nodes on hidden or locked
+    /// layers are left alone.
+    pub fn invert_selection(&mut self){
+        for node in self.nodes.iter_mut(){
+            let hidden = self.groups.iter()
+                .any(|group| group.collapsed && group.member_ids.contains(&node.id));
+            if !hidden {
+                node.set_selected(!node.is_selected);
+                self.groups_cache.clear();
+            }
+        }
+        self.unselect_unavailable();
+    }
+
+    /// Union of the bounds of the selected nodes, or `None` when fewer than
+    /// two nodes are selected.
+    fn selection_bounds(&self) -> Option<Rectangle>{
+        let mut selected = self.nodes.iter()
+            .filter(|node| node.is_selected && !node.pinned && !self.is_hidden(node))
+            .map(|node| node.bounds);
+        let first = selected.next()?;
+        let mut count = 1;
+        let (mut min, mut max) = (first.position(), Point::new(first.x + first.width, first.y + first.height));
+        for b in selected {
+            min = Point::new(min.x.min(b.x), min.y.min(b.y));
+            max = Point::new(max.x.max(b.x + b.width), max.y.max(b.y + b.height));
+            count += 1;
+        }
+        if count < 2 {
+            return None;
+        }
+        Some(Rectangle{
+            x: min.x,
+            y: min.y,
+            width: max.x - min.x,
+            height: max.y - min.y,
+        })
+    }
+
+    /// Dashed outline around the selected nodes, in world space.
+    fn draw_selection_bounds(&self, frame: &mut Frame){
+        if let Some(bounds) = self.selection_bounds() {
+            let margin = Self::SELECTION_MARGIN;
+            let outline = Path::rectangle(
+                Point::new(bounds.x - margin, bounds.y - margin),
+                Size::new(bounds.width + 2.0 * margin, bounds.height + 2.0 * margin),
+            );
+            let segments = [6.0 * self.scaling, 4.0 * self.scaling];
+            frame.stroke(&outline, Stroke{
+                color: self.theme.selection,
+                width: 1.5 * self.scaling,
+                line_dash: LineDash{ segments: &segments, offset: 0 },
+                ..Stroke::default()
+            });
+        }
+    }
+
+    /// Fill of a node, taking the breadth-first highlight and the
+    /// component colors into account.
+    fn node_fill(&self, node: &Node) -> Color{
+        self.highlight_color(node.id)
+            .or_else(|| self.component_colors.as_ref()?.get(&node.id).copied())
+            .unwrap_or_else(|| node.fill_color(&self.theme))
+    }
+
+    fn node(&self, id: u32) -> Option<&Node>{
+        self.nodes.iter().find(|x| x.id == id)
+    }
+
+    /// World-space route of an edge, from the center of its `from` node to
+    /// the border of its `to` node, following the current edge style.
+    /// Orthogonal routes run between the sides of the nodes instead. Ends
+    /// attached to a port start or end exactly on that port instead. Ends
+    /// hidden in a collapsed group attach to the group's box, and edges
+    /// between two members of the same collapsed group are not routed.
+    /// Neither are edges on a hidden layer or touching a node on one, nor
+    /// edges touching a pinned node, which has no place in world space.
+    fn edge_route(&self, edge: &Edge) -> Option<EdgeRoute>{
+        self.route_between(edge, self.node(edge.from)?, self.node(edge.to)?)
+    }
+
+    /// `edge_route` with the end nodes already looked up, for callers that
+    /// route every edge and index the nodes once.
+    fn route_between(&self, edge: &Edge, source: &Node, target: &Node) -> Option<EdgeRoute>{
+        if source.pinned || target.pinned {
+            return None;
+        }
+        if [&edge.layer, &source.layer, &target.layer].iter().any(|layer| !self.is_layer_visible(layer)) {
+            return None;
+        }
+        let source_group = self.collapsed_group_of(edge.from);
+        let target_group = self.collapsed_group_of(edge.to);
+        if let (Some(a), Some(b)) = (source_group, target_group) {
+            if a.id == b.id {
+                return None;
+            }
+        }
+        let from = match source_group {
+            Some(group) => group.bounds.center(),
+            None => edge.from_port.as_deref()
+                .and_then(|port| source.port_position(port))
+                .unwrap_or_else(|| source.bounds.center()),
+        };
+        let to_port = edge.to_port.as_deref()
+            .filter(|_| target_group.is_none())
+            .and_then(|port| target.port_position(port));
+        // Parallel edges bow apart, each by its lane.
+        let curvature = edge.lane * EdgeRoute::LANE_CURVATURE;
+        let bend = match self.edge_style {
+            EdgeStyle::Straight if curvature == 0.0 => Bend::Straight,
+            EdgeStyle::Straight | EdgeStyle::Curved => {
+                let curvature = match self.edge_style {
+                    EdgeStyle::Curved => curvature + EdgeRoute::CURVATURE,
+                    _ => curvature,
+                };
+                let center = to_port.unwrap_or_else(|| target.bounds.center());
+                let direction = center - from;
+                let midpoint = from + direction * 0.5;
+                Bend::Curve(midpoint + Vector::new(-direction.y, direction.x) * curvature)
+            }
+            EdgeStyle::Orthogonal => {
+                let from_port = edge.from_port.as_deref()
+                    .filter(|_| source_group.is_none())
+                    .and_then(|port| source.port_position(port));
+                return Some(EdgeRoute::orthogonal(
+                    source_group.map_or(source.bounds, |group| group.bounds),
+                    target_group.map_or(target.bounds, |group| group.bounds),
+                    from_port,
+                    to_port,
+                ));
+            }
+        };
+        let approach = match bend {
+            Bend::Curve(control) => control,
+            _ => from,
+        };
+        let to = to_port.unwrap_or_else(|| match target_group {
+            Some(group) => NodeShape::Rectangle.boundary_point(group.bounds, approach),
+            None => target.shape.boundary_point(target.bounds, approach),
+        });
+        Some(EdgeRoute{ from, to, bend })
+    }
+
+    /// Sets or clears the text shown at the middle of an edge.
+    pub fn set_edge_label(&mut self, edge_id: u32, label: Option<String>){
+        if let Some(edge) = self.edges.iter_mut().find(|x| x.id == edge_id) {
+            edge.label = label;
+            self.mark_dirty();
+        }
+    }
+
+    /// Adds a named port to a node, at `offset` from its top-left corner.
+    /// Returns false if the node does not exist or already has a port with
+    /// that name.
+    pub fn add_port(&mut self, node_id: u32, port: Port) -> bool{
+        match self.nodes.iter_mut().find(|x| x.id == node_id) {
+            Some(node) if !node.ports.iter().any(|p| p.name == port.name) => {
+                node.ports.push(port);
+                self.mark_dirty();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Attaches the ends of an edge to named ports of its nodes, or back to
+    /// the node outline with `None`.
+    pub fn set_edge_ports(&mut self, edge_id: u32, from_port: Option<String>, to_port: Option<String>){
+        if let Some(edge) = self.edges.iter_mut().find(|x| x.id == edge_id) {
+            edge.from_port = from_port;
+            edge.to_port = to_port;
+            self.mark_dirty();
+        }
+    }
+
+    /// Colors currently used to draw the network.
+    pub fn theme(&self) -> &Theme{
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme){
+        self.theme = theme;
+        self.request_redraw();
+    }
+
+    /// Replaces only the background of the current theme, e.g. to match
+    /// the surrounding app. Switching themes afterwards brings back the
+    /// preset's background.
+    pub fn set_background_color(&mut self, color: Color){
+        self.set_theme(Theme{ background: color, ..self.theme });
+    }
+
+    pub fn tool(&self) -> Tool{
+        self.tool
+    }
+
+    /// Switches what clicks on the canvas do. A measurement in progress or
+    /// shown is dropped; a pen stroke in progress is kept.
+    pub fn set_tool(&mut self, tool: Tool){
+        match self.interaction {
+            Interaction::Measuring { .. } => self.interaction = Interaction::None,
+            Interaction::Drawing => {
+                self.finish_stroke();
+                self.interaction = Interaction::None;
+            }
+            _ => {}
+        }
+        self.tool = tool;
+        self.measurement = None;
+        self.request_redraw();
+    }
+
+    /// Switches to `tool`, or back to selecting if it is already on.
+    fn toggle_tool(&mut self, tool: Tool){
+        self.set_tool(if self.tool == tool { Tool::Select } else { tool });
+    }
+
+    /// Changes how every edge is routed.
+    pub fn set_edge_style(&mut self, style: EdgeStyle){
+        self.edge_style = style;
+        self.request_redraw();
+    }
+
+    /// Returns the id of the edge passing within a few screen pixels of the
+    /// given world position. Edges on hidden or locked layers are skipped.
+    pub fn edge_at_screen(&self, world_pos: Point) -> Option<u32>{
+        let threshold = Self::EDGE_HIT_DISTANCE / self.scaling;
+        self.edges.iter()
+            .filter(|edge| self.is_layer_available(&edge.layer))
+            .filter_map(|edge| {
+                let route = self.edge_route(edge)?;
+                Some((edge.id, route.distance_to(world_pos)))
+            })
+            .filter(|(_, distance)| *distance <= threshold)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(id, _)| id)
+    }
+
+    /// Applies a change message. Ids come from the sender; the id counter is
+    /// moved past them so nodes and edges created here don't collide.
+    /// Messages about unknown ids, and additions with an id that is already
+    /// used or `u32::MAX`, are ignored.
+    pub fn update_message(&mut self, message: NetworkMessage){
+        match message {
+            NetworkMessage::Update | NetworkMessage::SelectionChanged(_) => return,
+            NetworkMessage::NodeAdded{ id, label, x, y } => {
+                if !self.claim_id(id) {
+                    return;
+                }
+                let mut node = Node::new(id, Rectangle{
+                    x,
+                    y,
+                    width: self.default_node_size.width,
+                    height: self.default_node_size.height,
+                });
+                node.label = label;
+                node.color = self.default_node_color;
+                self.nodes.push(node);
+            }
+            NetworkMessage::NodeMoved{ id, x, y } => {
+                match self.nodes.iter_mut().find(|n| n.id == id) {
+                    Some(node) => node.set_new_pos(Vector::new(x, y)),
+                    None => return,
+                }
+            }
+            NetworkMessage::NodeUpdated{ id, label } => {
+                match self.nodes.iter_mut().find(|n| n.id == id) {
+                    Some(node) => node.label = label,
+                    None => return,
+                }
+            }
+            NetworkMessage::NodeRemoved{ id } => {
+                self.remove_node(id);
+                return;
+            }
+            NetworkMessage::EdgeAdded{ id, from, to } => {
+                if self.check_edge(from, to).is_err() || !self.claim_id(id) {
+                    return;
+                }
+                self.edges.push(Edge::new(id, from, to));
+            }
+            NetworkMessage::EdgeRemoved{ id } => {
+                self.remove_edge(id);
+                return;
+            }
+            NetworkMessage::CursorMoved{ user, x, y } => {
+                self.set_remote_cursor(user, Point::new(x, y));
+                return;
+            }
+            NetworkMessage::Batch(messages) => {
+                for message in messages {
+                    self.update_message(message);
+                }
+                return;
+            }
+        }
+        self.mark_dirty();
+    }
+
+    /// Adds a node, giving it a fresh id, and returns that id.
+    pub fn add_node(&mut self, mut node: Node) -> u32{
+        node.id = self.allocate_id();
+        self.nodes.push(node);
+        self.mark_dirty();
+        self.nodes[self.nodes.len() - 1].id
+    }
+
+    /// Removes a node along with its edges and its place in any group.
+    /// Returns whether the node existed.
+    pub fn remove_node(&mut self, id: u32) -> bool{
+        let count = self.nodes.len();
+        self.nodes.retain(|n| n.id != id);
+        if self.nodes.len() == count {
+            return false;
+        }
+        self.edges.retain(|e| e.from != id && e.to != id);
+        for group in self.groups.iter_mut() {
+            group.member_ids.retain(|member| *member != id);
+        }
+        self.groups.retain(|group| !group.member_ids.is_empty());
+        self.mark_dirty();
+        true
+    }
+
+    /// Connects two nodes and returns the new edge's id. Refuses self-loops
+    /// and edges duplicating an existing one; see `check_edge`.
+    pub fn add_edge(&mut self, from: u32, to: u32) -> Result<u32, EdgeError>{
+        self.check_edge(from, to)?;
+        let id = self.allocate_id();
+        self.edges.push(Edge::new(id, from, to));
+        self.mark_dirty();
+        Ok(id)
+    }
+
+    /// Removes an edge. Returns whether the edge existed.
+    pub fn remove_edge(&mut self, id: u32) -> bool{
+        let count = self.edges.len();
+        self.edges.retain(|e| e.id != id);
+        if self.edges.len() == count {
+            return false;
+        }
+        self.mark_dirty();
+        true
+    }
+
+    /// The selected node, if exactly one node is selected.
+    pub fn selected_node(&self) -> Option<&Node>{
+        let mut selected = self.nodes.iter().filter(|node| node.is_selected);
+        match (selected.next(), selected.next()) {
+            (Some(node), None) => Some(node),
+            _ => None,
+        }
+    }
+
+    pub fn selected_node_count(&self) -> usize{
+        self.nodes.iter().filter(|node| node.is_selected).count()
+    }
+
+    /// Selects the node after the current one in id order, or the one
+    /// before it if `reverse`, wrapping around at the ends, and glides the
+    /// view to it. The current node is the single selected one, else the
+    /// one reached by the last call. Nodes in collapsed groups are skipped.
+    pub fn select_next_node(&mut self, reverse: bool){
+        let mut ids: Vec<u32> = self.nodes.iter()
+            .filter(|node| !self.is_hidden(node))
+            .map(|node| node.id)
+            .collect();
+        if ids.is_empty() {
+            return;
+        }
+        ids.sort_unstable();
+        let count = ids.len();
+        let current = self.selected_node().map(|node| node.id).or(self.tab_focus);
+        let index = match current.map(|id| ids.binary_search(&id)) {
+            Some(Ok(i)) if reverse => (i + count - 1) % count,
+            Some(Ok(i)) => (i + 1) % count,
+            // The current node is gone; continue from where it was.
+            Some(Err(i)) if reverse => (i + count - 1) % count,
+            Some(Err(i)) => i % count,
+            None if reverse => count - 1,
+            None => 0,
+        };
+        let id = ids[index];
+        self.unselect_all_nodes();
+        self.unselect_all_edges();
+        self.unselect_all_groups();
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.set_selected(true);
+        }
+        self.tab_focus = Some(id);
+        self.focus_node(id, None);
+        self.request_redraw();
+    }
+
+    pub fn set_node_label(&mut self, id: u32, label: String){
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.label = label;
+            self.mark_dirty();
+        }
+    }
+
+    /// Sets the fill of a node, or `None` to use the theme's node color.
+    pub fn set_node_color(&mut self, id: u32, color: Option<Color>){
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.color = color;
+            self.mark_dirty();
+        }
+    }
+
+    /// Moves and resizes a node. Its ports keep their place on its sides.
+    /// Bounds that aren't finite, or without a positive size, are ignored.
+    pub fn set_node_bounds(&mut self, id: u32, bounds: Rectangle){
+        let Rectangle{ x, y, width, height } = bounds;
+        if ![x, y, width, height].iter().all(|value| value.is_finite()) || width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.set_bounds(bounds);
+            self.mark_dirty();
+        }
+    }
+
+    /// Sets a metadata entry of a node, or removes it if `value` is `None`.
+    pub fn set_node_metadata(&mut self, id: u32, key: String, value: Option<String>){
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            match value {
+                Some(value) => node.metadata.insert(key, value),
+                None => node.metadata.remove(&key),
+            };
+            self.mark_dirty();
+        }
+    }
+
+    /// Sets or clears the icon drawn inside the node with the given id.
+    pub fn set_node_icon(&mut self, id: u32, icon: Option<Icon>){
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.icon = icon;
+            self.mark_dirty();
+        }
+    }
+
+    /// Switches every selected node to the next shape in the cycle
+    /// rectangle, ellipse, diamond.
+    pub fn cycle_selected_shapes(&mut self){
+        let mut changed = false;
+        for node in self.nodes.iter_mut().filter(|n| n.is_selected){
+            node.shape = node.shape.next();
+            changed = true;
+        }
+        if changed{
+            self.mark_dirty();
+        }
+    }
+
+    /// Locks the selected nodes, or unlocks them all if they are all locked
+    /// already.
+    pub fn toggle_selected_locks(&mut self){
+        let lock = self.nodes.iter().any(|n| n.is_selected && !n.locked);
+        let mut changed = false;
+        for node in self.nodes.iter_mut().filter(|n| n.is_selected){
+            changed |= node.locked != lock;
+            node.locked = lock;
+        }
+        if changed{
+            self.mark_dirty();
+        }
+    }
+
+    /// Turns a node to `radians`, clockwise on screen, around its center.
+    /// Only the drawing turns: clicks and edges still go by the upright
+    /// bounds. NaN and infinite angles are ignored.
+    pub fn set_node_rotation(&mut self, id: u32, radians: f32){
+        if !radians.is_finite() {
+            return;
+        }
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.rotation = radians.rem_euclid(std::f32::consts::TAU);
+            self.mark_dirty();
+        }
+    }
+
+    /// Sets how opaque a node is drawn, from 0 for invisible to 1 for
+    /// fully opaque, e.g. to de-emphasize it. Multiplies into the opacity
+    /// of all its colors. NaN and infinite values are ignored.
+    pub fn set_node_alpha(&mut self, id: u32, alpha: f32){
+        if !alpha.is_finite() {
+            return;
+        }
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.alpha = alpha.clamp(0.0, 1.0);
+            self.mark_dirty();
+        }
+    }
+
+    /// Turns the selected nodes that aren't locked by `radians` more.
+    pub fn rotate_selected(&mut self, radians: f32){
+        let mut changed = false;
+        for node in self.nodes.iter_mut().filter(|n| n.is_selected && !n.locked){
+            node.rotation = (node.rotation + radians).rem_euclid(std::f32::consts::TAU);
+            changed = true;
+        }
+        if changed{
+            self.mark_dirty();
+        }
+    }
+
+    /// Pins the selected nodes to the screen, or unpins them all if they are
+    /// all pinned already. Nodes keep their place and size on screen in a
+    /// canvas of `size`.
+    pub fn toggle_selected_pins(&mut self, size: Size){
+        let pin = self.nodes.iter().any(|n| n.is_selected && !n.pinned);
+        let camera = self.camera();
+        let factor = if pin { self.scaling } else { 1.0 / self.scaling };
+        let mut changed = false;
+        for node in self.nodes.iter_mut().filter(|n| n.is_selected && n.pinned != pin){
+            let corner = if pin {
+                camera.world_to_screen(node.bounds.position(), size)
+            } else {
+                camera.screen_to_world(node.bounds.position(), size)
+            };
+            node.set_bounds(Rectangle{
+                x: corner.x,
+                y: corner.y,
+                width: node.bounds.width * factor,
+                height: node.bounds.height * factor,
+            });
+            node.pinned = pin;
+            changed = true;
+        }
+        if changed{
+            self.mark_dirty();
+        }
+    }
+
+    /// Removes every selected edge from the network.
+    pub fn delete_selected_edges(&mut self){
+        let count = self.edges.len();
+        self.edges.retain(|e| !e.is_selected);
+        if self.edges.len() != count{
+            self.mark_dirty();
+        }
+    }
+
+    /// Mirrors the positions of the selected nodes across the centerline of
+    /// the selection. `Axis::Horizontal` flips left/right around the centroid's
+    /// x, `Axis::Vertical` flips top/bottom around the centroid's y. Locked
+    /// and pinned nodes are left out.
+    pub fn flip_selection(&mut self, axis: Axis){
+        let selected: Vec<&mut Node> = self.nodes.iter_mut()
+            .filter(|n| n.is_selected && !n.is_fixed())
+            .collect();
+        if selected.is_empty(){
+            return;
+        }
+        let count = selected.len() as f32;
+        let centroid = selected.iter()
+            .fold(Vector::new(0., 0.), |acc, n| {
+                let center = n.bounds.center();
+                acc + Vector::new(center.x, center.y)
+            }) * (1.0 / count);
+        for node in selected{
+            let center = node.bounds.center();
+            let mirrored = match axis {
+                Axis::Horizontal => Point::new(2.0 * centroid.x - center.x, center.y),
+                Axis::Vertical => Point::new(center.x, 2.0 * centroid.y - center.y),
+            };
+            node.set_new_pos(Vector::new(
+                mirrored.x - node.bounds.width / 2.0,
+                mirrored.y - node.bounds.height / 2.0,
+            ));
+        }
+        self.mark_dirty();
+    }
+
+    /// Moves every selected node that is not locked by `offset` in world space.
+    pub fn nudge_selection(&mut self, offset: Vector){
+        let mut moved = false;
+        for node in self.nodes.iter_mut().filter(|n| n.is_selected && !n.locked){
+            node.set_new_pos(node.get_pos() + offset);
+            moved = true;
+        }
+        if moved{
+            self.mark_dirty();
+        }
+    }
+
+    /// Renders the part of the world visible in a canvas of `view_size` into
+    /// an image of `width` x `height` pixels, stretching it if the aspect
+    /// ratios differ.
+    pub fn render_image(&self, view_size: Size, width: u32, height: u32) -> RgbaImage{
+        let mut raster = Raster::new(width, height, self.theme.background);
+        let factor = Vector::new(width as f32 / view_size.width, height as f32 / view_size.height);
+        let to_image = |world: Point| {
+            let screen = self.world_to_screen(world, view_size);
+            Point::new(screen.x * factor.x, screen.y * factor.y)
+        };
+        // Stroke widths are given in world units, so they scale like the shapes.
+        let stroke_factor = self.scaling * factor.x.min(factor.y);
+
+        for group in &self.groups{
+            let outline: Vec<Point> = group.outline().into_iter().map(to_image).collect();
+            if group.collapsed {
+                raster.fill_polygon(&outline, self.theme.node);
+            }
+            raster.stroke_polygon(&outline, 2.0 * self.scaling * stroke_factor, group.stroke_color(&self.theme));
+        }
+
+        for edge in &self.edges{
+            if let Some(route) = self.edge_route(edge) {
+                let color = edge.color(&self.theme);
+                let points: Vec<Point> = route.polyline().into_iter().map(to_image).collect();
+                for segment in points.windows(2) {
+                    raster.stroke_line(segment[0], segment[1], 5.0 * self.scaling * stroke_factor * edge.width, color);
+                }
+                if let Some(arrowhead) = Edge::arrowhead(route.approach(), route.to, self.scaling) {
+                    raster.fill_polygon(&arrowhead.map(to_image), color);
+                }
+                if let Some(label) = edge.label.as_deref().filter(|label| !label.is_empty()) {
+                    let size = Edge::LABEL_SIZE * stroke_factor;
+                    let padding = Edge::LABEL_PADDING * stroke_factor;
+                    let center = to_image(route.midpoint());
+                    let half = Vector::new(Raster::text_width(label, size) / 2.0 + padding, size / 2.0 + padding);
+                    let (min, max) = (center - half, center + half);
+                    let backdrop = [min, Point::new(max.x, min.y), max, Point::new(min.x, max.y)];
+                    raster.fill_polygon(&backdrop, self.theme.backdrop());
+                    raster.fill_text(label, center, size, color);
+                }
+            }
+        }
+
+        for node in self.nodes.iter().filter(|node| !node.pinned && !self.is_hidden(node)){
+            self.rasterize_node(&mut raster, node, to_image, stroke_factor, self.scaling);
+        }
+        // Pinned nodes are in screen space, on top of the rest.
+        let pixel_factor = factor.x.min(factor.y);
+        let screen_to_image = |screen: Point| Point::new(screen.x * factor.x, screen.y * factor.y);
+        for node in self.nodes.iter().filter(|node| node.pinned && !self.is_hidden(node)){
+            self.rasterize_node(&mut raster, node, screen_to_image, pixel_factor, 1.0);
+        }
+
+        // Annotations go above everything else, as on the canvas.
+        for annotation in &self.annotations {
+            let points: Vec<Point> = annotation.points.iter().copied().map(to_image).collect();
+            for segment in points.windows(2) {
+                raster.stroke_line(segment[0], segment[1], Annotation::WIDTH * self.scaling * stroke_factor, self.theme.annotation);
+            }
+        }
+        for text in &self.texts {
+            let content = self.edit_text(EditTarget::Text(text.id)).unwrap_or(&text.content);
+            let size = text.size * stroke_factor;
+            let corner = to_image(text.position);
+            for (row, line) in content.lines().enumerate() {
+                let center = corner + Vector::new(Raster::text_width(line, size) / 2.0, size * (row as f32 + 0.5));
+                raster.fill_text(line, center, size, text.color.unwrap_or(self.theme.annotation));
+            }
+        }
+
+        raster.image
+    }
+
+    /// Draws a node into an exported image. `to_image` maps the node's
+    /// coordinates to image pixels, `unit` is the image pixels per unit of
+    /// text and stroke, and `scale` the zoom its strokes are drawn at.
+    fn rasterize_node(&self, raster: &mut Raster, node: &Node, to_image: impl Fn(Point) -> Point, unit: f32, scale: f32){
+        let outline: Vec<Point> = node.shape.outline(node.bounds)
+            .into_iter()
+            .map(&to_image)
+            .collect();
+        raster.fill_polygon(&outline, self.node_fill(node));
+        raster.stroke_polygon(&outline, 2.5 * scale * unit, node.stroke_color(&self.theme));
+        if let Some((icon, area)) = node.icon_placement(){
+            let outline: Vec<Point> = icon.outline(area)
+                .into_iter()
+                .map(&to_image)
+                .collect();
+            raster.fill_polygon(&outline, self.theme.label);
+        }
+        if !node.label.is_empty() {
+            raster.fill_text(&node.label, to_image(node.bounds.center()), Node::LABEL_SIZE * unit, self.theme.label);
+        }
+    }
+
+    /// Writes the current view, as seen in a canvas of `view_size`, to a PNG
+    /// file of `width` x `height` pixels.
+    pub fn export_png(&self, path: impl AsRef<std::path::Path>, view_size: Size, width: u32, height: u32) -> image::ImageResult<()>{
+        self.render_image(view_size, width, height).save(path)
+    }
+
+    /// Zooms to `scaling`, clamped to the zoom limits, keeping the world
+    /// point under `focus` in place. `focus` is a screen offset from the
+    /// center of the canvas. Returns whether the zoom changed.
+    pub fn zoom_at(&mut self, scaling: f32, focus: Point) -> bool{
+        let scaling = scaling.clamp(self.min_scaling, self.max_scaling);
+        if scaling == self.scaling {
+            return false;
+        }
+        let focus = Vector::new(focus.x, focus.y);
+        self.translation = self.translation + focus * (1.0 / scaling - 1.0 / self.scaling);
+        self.scaling = scaling;
+        self.clamp_translation();
+        self.request_redraw();
+        true
+    }
+
+    /// Moves the view by a distance given in screen pixels.
+    fn pan_by(&mut self, offset: Vector){
+        if offset != Vector::new(0.0, 0.0) {
+            self.translation = self.translation + offset * (1.0 / self.scaling);
+            self.clamp_translation();
+            self.request_redraw();
+        }
+    }
+
+    /// Keeps the center of the view inside `bounds`, given in world
+    /// coordinates, so panning and zooming can't lose the content. `None`
+    /// lets the view go anywhere.
+    pub fn set_pan_bounds(&mut self, bounds: Option<Rectangle>){
+        self.pan_bounds = bounds;
+        self.clamp_translation();
+        self.request_redraw();
+    }
+
+    /// Limits panning to the area the nodes currently cover. Does nothing
+    /// if there are no nodes.
+    pub fn bound_pan_to_nodes(&mut self){
+        let extent = Self::extent(self.nodes.iter());
+        if extent.is_some() {
+            self.set_pan_bounds(extent);
+        }
+    }
+
+    /// Smallest rectangle around the bounds of `nodes`, or `None` if there
+    /// are none.
+    pub(super) fn extent<'a>(nodes: impl Iterator<Item = &'a Node>) -> Option<Rectangle>{
+        nodes.map(|node| node.bounds).reduce(|a, b| {
+            let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+            Rectangle{
+                x,
+                y,
+                width: (a.x + a.width).max(b.x + b.width) - x,
+                height: (a.y + a.height).max(b.y + b.height) - y,
+            }
+        })
+    }
+
+    /// Moves the translation back inside the pan bounds. Returns whether it
+    /// had to move.
+    fn clamp_translation(&mut self) -> bool{
+        let bounds = match self.pan_bounds {
+            Some(bounds) => bounds,
+            None => return false,
+        };
+        // The world point at the center of the view is `-translation`.
+        let clamped = Vector::new(
+            self.translation.x.clamp(-(bounds.x + bounds.width), -bounds.x),
+            self.translation.y.clamp(-(bounds.y + bounds.height), -bounds.y),
+        );
+        let moved = clamped != self.translation;
+        self.translation = clamped;
+        moved
+    }
+
+    /// Moves the dragged node to `new_pos` and the rest of the selection
+    /// along with it. Locked nodes stay put.
+    fn drag_selection(&mut self, node_id: u32, new_pos: Vector){
+        let (offset, pinned) = match self.node(node_id) {
+            Some(node) => (new_pos - node.get_pos(), node.pinned),
+            None => return,
+        };
+        if offset == Vector::new(0.0, 0.0) {
+            return;
+        }
+        // Pinned nodes move in screen pixels, the others in world units.
+        let scaling = self.scaling;
+        for node in self.nodes.iter_mut().filter(|n| (n.id == node_id || n.is_selected) && !n.locked){
+            let offset = match (pinned, node.pinned) {
+                (true, false) => offset * (1.0 / scaling),
+                (false, true) => offset * scaling,
+                _ => offset,
+            };
+            node.set_new_pos(node.get_pos() + offset);
+        }
+        self.mark_dirty();
+    }
+
+    /// Aborts the drag in progress, putting the dragged node or the view back
+    /// where it started. Returns whether there was anything to cancel.
+    fn cancel_interaction(&mut self) -> bool{
+        match std::mem::replace(&mut self.interaction, Interaction::None) {
+            Interaction::None => return false,
+            Interaction::PanningScreen { translation, .. } => {
+                // The view isn't part of the document.
+                self.translation = translation;
+                self.stop_inertia();
+                self.request_redraw();
+                return true;
+            }
+            Interaction::PanningNode { node_id, translation, .. } => {
+                self.drag_selection(node_id, translation);
+            }
+            Interaction::PanningGroup { group_id, translation, .. } => {
+                self.move_group(group_id, translation);
+            }
+            Interaction::Measuring { .. } | Interaction::ReconnectingEdge { .. } => {
+                self.request_redraw();
+                return true;
+            }
+            Interaction::Drawing => self.discard_stroke(),
+        }
+        self.mark_dirty();
+        true
+    }
+
+    fn handle_key(&mut self, key_code: KeyCode, modifiers: keyboard::Modifiers, bounds: Rectangle) -> (event::Status, Option<NetworkMessage>) {
+        if self.searching {
+            return self.handle_search_key(key_code);
+        }
+        if self.label_edit.is_some() {
+            return self.handle_edit_key(key_code);
+        }
+        let step = if modifiers.shift() {
+            Self::GRID_SIZE * Self::LARGE_NUDGE_STEPS
+        } else {
+            Self::GRID_SIZE
+        };
+        // Shift rotates by single degrees.
+        let rotation = if modifiers.shift() { 1.0 } else { Self::ROTATION_STEP }.to_radians();
+        match key_code {
+            KeyCode::Escape if self.tool != Tool::Select && matches!(self.interaction, Interaction::None) => {
+                self.set_tool(Tool::Select);
+            }
+            KeyCode::Escape => {
+                if !self.cancel_interaction() {
+                    return (event::Status::Ignored, None);
+                }
+            }
+            KeyCode::Tab => self.select_next_node(modifiers.shift()),
+            KeyCode::A if modifiers.control() => self.select_all(),
+            KeyCode::I if modifiers.control() => self.invert_selection(),
+            KeyCode::Left if modifiers.alt() => self.align_selected(AlignKind::Left),
+            KeyCode::Right if modifiers.alt() => self.align_selected(AlignKind::Right),
+            KeyCode::Up if modifiers.alt() => self.align_selected(AlignKind::Top),
+            KeyCode::Down if modifiers.alt() => self.align_selected(AlignKind::Bottom),
+            KeyCode::H if modifiers.alt() && modifiers.shift() => self.distribute_selected(Axis::Horizontal),
+            KeyCode::V if modifiers.alt() && modifiers.shift() => self.distribute_selected(Axis::Vertical),
+            KeyCode::H if modifiers.alt() => self.align_selected(AlignKind::HorizontalCenter),
+            KeyCode::V if modifiers.alt() => self.align_selected(AlignKind::VerticalCenter),
+            KeyCode::H => self.flip_selection(Axis::Horizontal),
+            KeyCode::V => self.flip_selection(Axis::Vertical),
+            KeyCode::Left => self.nudge_selection(Vector::new(-step, 0.)),
+            KeyCode::Right => self.nudge_selection(Vector::new(step, 0.)),
+            KeyCode::Up => self.nudge_selection(Vector::new(0., -step)),
+            KeyCode::Down => self.nudge_selection(Vector::new(0., step)),
+            KeyCode::Delete => self.delete_selected_edges(),
+            KeyCode::LBracket => self.rotate_selected(-rotation),
+            KeyCode::RBracket => self.rotate_selected(rotation),
+            KeyCode::L if modifiers.shift() => self.apply_layered_layout(),
+            KeyCode::L => self.apply_force_layout(Self::FORCE_LAYOUT_ITERATIONS),
+            KeyCode::O if !modifiers.control() => self.apply_circular_layout(),
+            KeyCode::R if modifiers.control() => self.set_rulers_visible(!self.show_rulers),
+            KeyCode::X => self.set_axes_visible(!self.show_axes),
+            KeyCode::R => self.apply_grid_layout(Self::GRID_LAYOUT_SPACING),
+            KeyCode::S if !modifiers.control() => self.cycle_selected_shapes(),
+            KeyCode::E if modifiers.shift() && !modifiers.control() => self.cycle_selected_line_styles(),
+            KeyCode::E if !modifiers.control() => self.set_edge_style(self.edge_style.next()),
+            KeyCode::G if modifiers.shift() && !modifiers.control() => self.ungroup_selected(),
+            KeyCode::G if !modifiers.control() => {
+                self.group_selected();
+            }
+            KeyCode::C if modifiers.shift() => self.toggle_component_colors(),
+            KeyCode::D => self.toggle_size_by_degree(),
+            KeyCode::C => self.toggle_selected_groups(),
+            KeyCode::K if modifiers.shift() => self.toggle_selected_pins(bounds.size()),
+            KeyCode::K => self.toggle_selected_locks(),
+            KeyCode::M if !modifiers.control() => self.toggle_tool(Tool::Measure),
+            KeyCode::W if modifiers.shift() => self.clear_annotations(),
+            KeyCode::W => self.toggle_tool(Tool::Pen),
+            KeyCode::B => self.highlight_reachable(),
+            KeyCode::T if modifiers.shift() => self.toggle_tool(Tool::Text),
+            KeyCode::T => self.set_theme(self.theme.toggled()),
+            KeyCode::P if modifiers.control() => {
+                let size = bounds.size();
+                let (width, height) = ((size.width * self.export_scale).round() as u32, (size.height * self.export_scale).round() as u32);
+                if let Err(error) = self.export_png(Self::EXPORT_PATH, size, width, height) {
+                    println!("Could not export the view to {}: {}", Self::EXPORT_PATH, error);
+                }
+            }
+            KeyCode::F if modifiers.control() => self.start_search(),
+            KeyCode::F if modifiers.shift() => self.fit_selection(bounds.size()),
+            KeyCode::F => self.fit_all(bounds.size()),
+            KeyCode::F2 => self.start_label_edit(),
+            KeyCode::F3 => self.set_frame_stats_visible(!self.show_frame_stats),
+            KeyCode::G if modifiers.control() => {
+                if let Err(error) = std::fs::write(Self::GRAPHML_PATH, self.export_graphml()) {
+                    println!("Could not export the graph to {}: {}", Self::GRAPHML_PATH, error);
+                }
+            }
+            // Shift treats edges as undirected.
+            KeyCode::M if modifiers.control() => {
+                let csv = self.export_adjacency_csv(!modifiers.shift());
+                if let Err(error) = std::fs::write(Self::ADJACENCY_PATH, csv) {
+                    println!("Could not export the adjacency matrix to {}: {}", Self::ADJACENCY_PATH, error);
+                }
+            }
+            _ => return (event::Status::Ignored, None),
+        }
+        (event::Status::Captured, None)
+    }
+
+    fn handle_event(
+        &mut self,
+        event: Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (event::Status, Option<NetworkMessage>) {
+        if let Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) = event {
+            self.modifiers = modifiers;
+            return (event::Status::Ignored, None);
+        }
+
+        // Releasing Space ends its pan even if focus moved elsewhere.
+        if let Event::Keyboard(keyboard::Event::KeyReleased { key_code: KeyCode::Space, .. }) = event {
+            self.space_held = false;
+            if let Interaction::PanningScreen { .. } = self.interaction {
+                self.release_pan();
+                self.interaction = Interaction::None;
+            }
+            return (event::Status::Ignored, None);
+        }
+
+        if let Event::Keyboard(_) = event {
+            if !self.focused {
+                return (event::Status::Ignored, None);
+            }
+        }
+
+        if let Event::Keyboard(keyboard::Event::KeyPressed { key_code: KeyCode::Space, .. }) = event {
+            if !self.searching && self.label_edit.is_none() {
+                self.space_held = true;
+                return (event::Status::Captured, None);
+            }
+        }
+
+        if let Event::Keyboard(keyboard::Event::KeyPressed { key_code, modifiers }) = event {
+            self.modifiers = modifiers;
+            return self.handle_key(key_code, modifiers, bounds);
+        }
+
+        if let Event::Keyboard(keyboard::Event::CharacterReceived(c)) = event {
+            if self.searching {
+                self.handle_search_character(c);
+                return (event::Status::Captured, None);
+            }
+            if self.label_edit.is_some() {
+                self.handle_edit_character(c);
+                return (event::Status::Captured, None);
+            }
+            return (event::Status::Ignored, None);
+        }
+
+        match event {
+            Event::Mouse(mouse_event) => {
+                if let mouse::Event::ButtonPressed(_) = mouse_event {
+                    self.focused = cursor.is_over(&bounds);
+                }
+                let (status, actions) = self.interpret(mouse_event, bounds, cursor);
+                for action in actions {
+                    self.perform(action);
+                }
+                (status, None)
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+}
+
+impl std::fmt::Debug for Network{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Network")
+            .field("nodes", &self.nodes.len())
+            .field("edges", &self.edges.len())
+            .field("current_path", &self.current_path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Network{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl iced_graphics::canvas::Program<NetworkMessage> for Network{
+    fn update(
+        &mut self,
+        event: Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (event::Status, Option<NetworkMessage>) {
+        if let Some(deferred) = self.defer_cursor_move(&event, bounds, cursor) {
+            return deferred;
+        }
+        self.flush_cursor_move();
+        let view = (self.translation, self.scaling);
+        let was_animating = self.is_animating();
+        let selection = self.selected_node_ids();
+        let dragged = match self.interaction {
+            Interaction::PanningNode{ node_id, .. } if self.drag_started => Some(node_id),
+            _ => None,
+        };
+        let (status, message) = self.handle_event(event, bounds, cursor);
+        if view != (self.translation, self.scaling) {
+            self.notify_view_changed();
+        }
+        let mut messages: Vec<NetworkMessage> = message.into_iter().collect();
+        if selection != self.selected_node_ids() {
+            messages.push(NetworkMessage::SelectionChanged(self.selected_node_ids()));
+        }
+        // The whole selection moved with the dragged node, as in
+        // `drag_selection`.
+        if let Some(dragged) = dragged.filter(|_| !matches!(self.interaction, Interaction::PanningNode{ .. })) {
+            messages.extend(self.nodes.iter()
+                .filter(|node| (node.id == dragged || node.is_selected) && !node.locked)
+                .map(|node| NetworkMessage::NodeMoved{ id: node.id, x: node.bounds.x, y: node.bounds.y }));
+        }
+        // Let the host know so it can resume its tick subscription.
+        if messages.is_empty() && !was_animating && self.is_animating() {
+            messages.push(NetworkMessage::Update);
+        }
+        let message = match messages.len() {
+            0 => None,
+            1 => messages.pop(),
+            _ => Some(NetworkMessage::Batch(messages)),
+        };
+        (status, message)
+    }
+
+    fn draw(&self, bounds: Rectangle, cursor: Cursor) -> Vec<Geometry> {
+        let started = Instant::now();
+        self.frame_stats.frame_started(started);
+        let center = Vector::new(bounds.width / 2.0, bounds.height / 2.0);
+        let mut frame = Frame::new(bounds.size());
+        let background = Path::rectangle(Point::ORIGIN, frame.size());
+        frame.fill(&background, self.theme.background);
+
+        let size = bounds.size();
+        // Under everything else, so they don't get in the way.
+        if self.show_axes {
+            self.draw_axes(&mut frame, size);
+        }
+        let detailed = self.scaling >= self.lod_threshold;
+        let dimmed = self.theme.dimmed();
+        let style = |node: &Node| if self.is_filtered_out(node) {
+            (Theme::faded(self.node_fill(node)), dimmed)
+        } else {
+            (self.node_fill(node), self.theme)
+        };
+        let to_world = |frame: &mut Frame| {
+            frame.translate(center);
+            frame.scale(self.scaling);
+            frame.translate(self.translation);
+        };
+        // Cached geometry is in screen space, so every item is drawn again
+        // when the view changes, but only the items that changed otherwise.
+        let view = DrawKey::new()
+            .vector(self.translation)
+            .float(self.scaling)
+            .size(size)
+            .value(detailed);
+        let settled = self.last_view.replace(Some(view.finish())) == Some(view.finish());
+        let mut scene = Scene::new(size, settled);
+
+        let groups = self.groups_cache.draw(size, |frame| {
+            frame.with_save(|frame| {
+                to_world(frame);
+                for group in self.groups.iter().filter(|group| self.is_in_view(group.bounds, size)){
+                    group.draw(frame, self.scaling, &self.theme, detailed);
+                }
+            });
+        });
+        let mut geometry = vec![frame.into_geometry(), groups];
+
+        let nodes: HashMap<u32, &Node> = self.nodes.iter().map(|node| (node.id, node)).collect();
+        for edge in &self.edges{
+            let route = match (nodes.get(&edge.from), nodes.get(&edge.to)) {
+                (Some(source), Some(target)) => self.route_between(edge, source, target),
+                _ => None,
+            };
+            if let Some(route) = route.filter(|route| self.is_in_view(route.bounds(), size)) {
+                let label = self.edit_text(EditTarget::Edge(edge.id));
+                let theme = if self.is_edge_filtered_out(edge) { &dimmed } else { &self.theme };
+                let key = edge.draw_key(&view, &route, label, theme);
+                scene.item(&edge.cache, key, |frame| {
+                    frame.with_save(|frame| {
+                        to_world(frame);
+                        edge.draw(frame, &route, label, self.scaling, theme, detailed);
+                    });
+                });
+            }
+        }
+
+        scene.uncached(|frame| {
+            frame.with_save(|frame| {
+                to_world(frame);
+                self.draw_selection_bounds(frame);
+            });
+        });
+
+        for node in self.drawn_nodes(size){
+            let (fill, theme) = style(node);
+            let label = self.edit_text(EditTarget::Node(node.id));
+            let key = node.draw_key(&view, label, fill, &theme);
+            scene.item(&node.cache, key, |frame| {
+                frame.with_save(|frame| {
+                    to_world(frame);
+                    if detailed {
+                        node.draw(frame, label, fill, self.scaling, &theme);
+                    } else {
+                        node.draw_dot(frame, fill, &theme);
+                    }
+                });
+            });
+        }
+
+        // Pinned nodes are in screen space, on top of the rest, and don't
+        // depend on the view.
+        for node in self.nodes.iter().filter(|node| node.pinned && !self.is_hidden(node)){
+            let (fill, theme) = style(node);
+            let label = self.edit_text(EditTarget::Node(node.id));
+            let key = node.draw_key(&DrawKey::new().size(size), label, fill, &theme);
+            scene.item(&node.cache, key, |frame| node.draw(frame, label, fill, 1.0, &theme));
+        }
+        geometry.extend(scene.into_geometry());
+
+        // Strokes grow while the pen moves, so they aren't cached.
+        let mut annotations = Frame::new(bounds.size());
+        annotations.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(self.scaling);
+            frame.translate(self.translation);
+            self.draw_annotations(frame);
+        });
+
+        let mut overlay = Frame::new(bounds.size());
+        self.draw_measurement(&mut overlay, bounds, cursor);
+        self.draw_edge_ends(&mut overlay, bounds, cursor);
+        self.draw_guides(&mut overlay, size);
+        self.draw_template_preview(&mut overlay, bounds, cursor);
+        self.draw_remote_cursors(&mut overlay, bounds);
+        if self.show_rulers {
+            self.draw_rulers(&mut overlay, bounds, cursor);
+        }
+        if self.searching {
+            self.draw_search_overlay(&mut overlay);
+        }
+        if self.show_frame_stats {
+            self.draw_frame_stats(&mut overlay);
+        }
+
+        geometry.extend([annotations.into_geometry(), overlay.into_geometry()]);
+        self.frame_stats.frame_finished(started);
+        geometry
+    }
+
+    fn mouse_interaction(&self, bounds: Rectangle, cursor: Cursor) -> mouse::Interaction {
+        if self.template_drag.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+        match self.interaction {
+            Interaction::PanningScreen { .. }
+            | Interaction::PanningNode { .. }
+            | Interaction::PanningGroup { .. }
+            | Interaction::ReconnectingEdge { .. } => return mouse::Interaction::Grabbing,
+            Interaction::Measuring { .. } | Interaction::Drawing => return mouse::Interaction::Crosshair,
+            Interaction::None if self.space_held => return mouse::Interaction::Grab,
+            Interaction::None if self.tool != Tool::Select => return mouse::Interaction::Crosshair,
+            Interaction::None => {}
+        }
+        let (cursor_position, world_position) = match cursor.position_in(&bounds) {
+            Some(position) => (position, self.screen_to_world(position, bounds.size())),
+            None => return mouse::Interaction::default(),
+        };
+        if self.edge_end_at(world_position).is_some() {
+            return mouse::Interaction::Grab;
+        }
+        if let Some(node) = self.get_node_at_screen(cursor_position, bounds.size()).and_then(|id| self.node(id)) {
+            return if node.locked {
+                mouse::Interaction::Pointer
+            } else {
+                mouse::Interaction::Grab
+            };
+        }
+        if self.edge_at_screen(world_position).is_some() {
+            mouse::Interaction::Pointer
+        } else if self.group_at(world_position).is_some() {
+            mouse::Interaction::Grab
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+/// World area shown in a canvas, see `Network::visible_region`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl Region {
+    /// Left edge, in world coordinates.
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    /// Top edge, in world coordinates.
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Whether `bounds` overlaps the region grown by `margin` on every side.
+    fn intersects(&self, bounds: Rectangle, margin: f32) -> bool {
+        bounds.x <= self.x + self.width + margin
+            && bounds.x + bounds.width >= self.x - margin
+            && bounds.y <= self.y + self.height + margin
+            && bounds.y + bounds.height >= self.y - margin
+    }
+}
+
+pub struct Node{
+    id: u32,
+    bounds: Rectangle,
+    /// Fill color, or `None` to use the theme's node color.
+    color: Option<Color>,
+    is_selected: bool,
+    icon: Option<Icon>,
+    label: String,
+    shape: NodeShape,
+    ports: Vec<Port>,
+    /// Locked nodes can be selected but not dragged, nudged or laid out.
+    locked: bool,
+    /// Free-form key/value properties, edited in the host's side panel.
+    metadata: HashMap<String, String>,
+    /// Labels for filtering the view, see `Network::set_tag_filter`.
+    tags: Vec<String>,
+    /// Name of the layer the node is on.
+    layer: String,
+    /// Pinned nodes keep their bounds in screen space, so they stay put
+    /// while the view pans and zooms, e.g. for legends and titles.
+    pinned: bool,
+    /// Clockwise turn of the drawn node around its center, in radians.
+    rotation: f32,
+    /// Opacity multiplied into the node's colors, in [0, 1].
+    alpha: f32,
+    cache: GeometryCache,
+}
+
+/// Named anchor on a node that edges can attach to instead of the node's
+/// center.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Port{
+    pub name: String,
+    /// Position relative to the node's top-left corner.
+    pub offset: Vector,
+}
+
+impl Port{
+    pub fn new(name: impl Into<String>, offset: Vector) -> Self{
+        Port{ name: name.into(), offset }
+    }
+
+    /// Ports at the middle of each side of a node of the given size.
+    pub fn sides(size: Size) -> Vec<Port>{
+        vec![
+            Port::new("top", Vector::new(size.width / 2.0, 0.0)),
+            Port::new("right", Vector::new(size.width, size.height / 2.0)),
+            Port::new("bottom", Vector::new(size.width / 2.0, size.height)),
+            Port::new("left", Vector::new(0.0, size.height / 2.0)),
+        ]
+    }
+}
+
+impl Node {
+    const LABEL_SIZE: f32 = 16.0;
+    const PORT_RADIUS: f32 = 5.0;
+    const LOCK_SIZE: f32 = 12.0;
+
+    fn new(id: u32, bounds: Rectangle) -> Self{
+        Node{
+            id,
+            bounds,
+            color: None,
+            is_selected: false,
+            icon: None,
+            label: String::new(),
+            shape: NodeShape::default(),
+            ports: Port::sides(bounds.size()),
+            locked: false,
+            metadata: HashMap::new(),
+            tags: Vec::new(),
+            layer: String::from(Layer::DEFAULT),
+            pinned: false,
+            rotation: 0.0,
+            alpha: 1.0,
+            cache: GeometryCache::default(),
+        }
+    }
+
+    /// A node covering `bounds` in world coordinates, to hand to
+    /// `Network::add_node`, which gives it its id.
+    pub fn with_bounds(bounds: Rectangle) -> Self{
+        Node::new(0, bounds)
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self{
+        self.label = label.into();
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self{
+        self.color = Some(color);
+        self
+    }
+
+    pub fn id(&self) -> u32{
+        self.id
+    }
+
+    pub fn bounds(&self) -> Rectangle{
+        self.bounds
+    }
+
+    pub fn label(&self) -> &str{
+        &self.label
+    }
+
+    /// Fill color, or `None` if the node uses the theme's node color.
+    pub fn color(&self) -> Option<Color>{
+        self.color
+    }
+
+    pub fn metadata(&self) -> &HashMap<String, String>{
+        &self.metadata
+    }
+
+    pub fn tags(&self) -> &[String]{
+        &self.tags
+    }
+
+    pub fn layer(&self) -> &str{
+        &self.layer
+    }
+
+    pub fn is_pinned(&self) -> bool{
+        self.pinned
+    }
+
+    /// Clockwise turn around the center, in radians.
+    pub fn rotation(&self) -> f32{
+        self.rotation
+    }
+
+    /// Opacity, from 0 for invisible to 1 for fully opaque.
+    pub fn alpha(&self) -> f32{
+        self.alpha
+    }
+
+    /// Whether layouts must leave the node where it is.
+    fn is_fixed(&self) -> bool{
+        self.locked || self.pinned
+    }
+
+    /// World position of the named port.
+    fn port_position(&self, name: &str) -> Option<Point>{
+        self.ports.iter()
+            .find(|port| port.name == name)
+            .map(|port| self.bounds.position() + port.offset)
+    }
+
+    /// The node's icon and the area it is drawn into, if it has one.
+    fn icon_placement(&self) -> Option<(Icon, Rectangle)>{
+        self.icon.map(|icon| (icon, Icon::placement(self.bounds)))
+    }
+
+    /// Draws the node. `label` overrides the node's own label, e.g. while it
+    /// is being edited, and `fill` its color.
+    fn draw(&self, frame: &mut Frame, label: Option<&str>, fill: Color, scale: f32, theme: &Theme) {
+        let theme = &theme.with_alpha(self.alpha);
+        let fill = Theme::translucent(fill, self.alpha);
+        let body = self.shape.path(self.bounds);
+        let stroke = Stroke{
+            color: self.stroke_color(theme),
+            width: 2.5 * scale,
+            ..Stroke::default()
+        };
+        // The ports stay upright since edges attach to them there, and so
+        // does the label, as canvas text can't be rotated.
+        frame.with_save(|frame| {
+            if self.rotation != 0.0{
+                let center = self.bounds.center();
+                frame.translate(Vector::new(center.x, center.y));
+                frame.rotate(self.rotation);
+                frame.translate(Vector::new(-center.x, -center.y));
+            }
+            frame.fill(&body, fill);
+            frame.stroke(&body, stroke);
+            if let Some((icon, area)) = self.icon_placement(){
+                frame.fill(&icon.path(area), theme.label);
+            }
+            if self.locked{
+                self.draw_lock(frame, scale, theme);
+            }
+        });
+        if self.is_selected{
+            for port in &self.ports{
+                let marker = Path::circle(self.bounds.position() + port.offset, Self::PORT_RADIUS * scale);
+                frame.fill(&marker, theme.label);
+                frame.stroke(&marker, Stroke{
+                    color: theme.selection,
+                    width: 1.5 * scale,
+                    ..Stroke::default()
+                });
+            }
+        }
+        let label = label.unwrap_or(&self.label);
+        if !label.is_empty(){
+            frame.fill_text(Text{
+                content: label.to_string(),
+                position: self.bounds.center(),
+                color: theme.label,
+                size: Self::LABEL_SIZE * scale,
+                horizontal_alignment: alignment::Horizontal::Center,
+                vertical_alignment: alignment::Vertical::Center,
+                ..Text::default()
+            });
+        }
+    }
+
+    /// Key of what `draw` or `draw_dot` would draw with the same
+    /// arguments, on top of the view's key.
+    fn draw_key(&self, view: &DrawKey, label: Option<&str>, fill: Color, theme: &Theme) -> u64{
+        let mut key = view.clone()
+            .rectangle(self.bounds)
+            .color(fill)
+            .value(label.unwrap_or(&self.label))
+            .value((self.is_selected, self.locked, self.shape, self.icon))
+            .float(self.rotation)
+            .float(self.alpha)
+            .theme(theme);
+        if self.is_selected {
+            for port in &self.ports {
+                key = key.vector(port.offset);
+            }
+        }
+        key.finish()
+    }
+
+    /// Cheap stand-in for `draw` when zoomed far out: a filled circle in the
+    /// node's color, or the selection color, with no stroke or text.
+    fn draw_dot(&self, frame: &mut Frame, fill: Color, theme: &Theme) {
+        let radius = self.bounds.width.min(self.bounds.height) / 2.0;
+        let color = if self.is_selected { theme.selection } else { fill };
+        frame.fill(&Path::circle(self.bounds.center(), radius), Theme::translucent(color, self.alpha));
+    }
+
+    /// Small padlock in the top-right corner of a locked node.
+    fn draw_lock(&self, frame: &mut Frame, scale: f32, theme: &Theme){
+        let size = Self::LOCK_SIZE;
+        let corner = Point::new(self.bounds.x + self.bounds.width - size * 1.5, self.bounds.y + size * 0.5);
+        let body_top = corner.y + size * 0.5;
+        frame.fill(&Path::rectangle(Point::new(corner.x, body_top), Size::new(size, size * 0.6)), theme.label);
+        let shackle = Path::new(|builder| {
+            builder.move_to(Point::new(corner.x + size * 0.2, body_top));
+            builder.line_to(Point::new(corner.x + size * 0.2, corner.y));
+            builder.line_to(Point::new(corner.x + size * 0.8, corner.y));
+            builder.line_to(Point::new(corner.x + size * 0.8, body_top));
+        });
+        frame.stroke(&shackle, Stroke{
+            color: theme.label,
+            width: 1.5 * scale,
+            ..Stroke::default()
+        });
+    }
+
+    fn fill_color(&self, theme: &Theme) -> Color {
+        self.color.unwrap_or(theme.node)
+    }
+
+    fn stroke_color(&self, theme: &Theme) -> Color {
+        if self.is_selected{
+            theme.selection
+        }else{
+            self.fill_color(theme)
+        }
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        self.shape.contains(self.bounds, point)
+    }
+
+    fn set_selected(&mut self, selected: bool){
+        self.is_selected = selected;
+    }
+
+    fn set_new_pos(&mut self, new_pos: Vector){
+        self.bounds.x = new_pos.x;
+        self.bounds.y = new_pos.y;
+    }
+
+    fn get_pos(&self) -> Vector{
+        Vector::new(self.bounds.x, self.bounds.y)
+    }
+
+    /// Resizes the node around its center, moving the ports along with the
+    /// sides they sit on.
+    fn set_size(&mut self, size: Size){
+        let center = self.bounds.center();
+        self.set_bounds(Rectangle{
+            x: center.x - size.width / 2.0,
+            y: center.y - size.height / 2.0,
+            width: size.width,
+            height: size.height,
+        });
+    }
+
+    /// Moves and resizes the node, moving the ports along with the sides
+    /// they sit on.
+    fn set_bounds(&mut self, bounds: Rectangle){
+        let old = self.bounds;
+        for port in self.ports.iter_mut() {
+            port.offset = Vector::new(
+                if old.width > 0.0 { port.offset.x * bounds.width / old.width } else { bounds.width / 2.0 },
+                if old.height > 0.0 { port.offset.y * bounds.height / old.height } else { bounds.height / 2.0 },
+            );
+        }
+        self.bounds = bounds;
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use iced::{Point, Rectangle, Vector};
+
+    use super::{AlignKind, Axis, Layer, Network, Node, NodeShape};
+
+    fn square(x: f32, y: f32, side: f32) -> Node{
+        Node::with_bounds(Rectangle{ x, y, width: side, height: side })
+    }
+
+    #[test]
+    fn horizontal_flip_mirrors_around_the_centroid_x(){
+        let mut network = Network::empty();
+        let ids = [
+            network.add_node(square(0.0, 0.0, 100.0)),
+            network.add_node(square(250.0, 40.0, 50.0)),
+            network.add_node(square(80.0, 300.0, 20.0)),
+        ];
+        let centers: Vec<_> = ids.iter().map(|id| network.node(*id).unwrap().bounds.center()).collect();
+        let centroid_x = centers.iter().map(|center| center.x).sum::<f32>() / 3.0;
+        network.select_all();
+        network.flip_selection(Axis::Horizontal);
+        for (id, before) in ids.iter().zip(&centers) {
+            let after = network.node(*id).unwrap().bounds.center();
+            assert!((after.x - (2.0 * centroid_x - before.x)).abs() < 1e-3, "{} moved to {:?}", id, after);
+            assert_eq!(after.y, before.y);
+        }
+    }
+
+    #[test]
+    fn bulk_moves_leave_locked_nodes_alone(){
+        let mut network = Network::empty();
+        let free = network.add_node(square(0.0, 0.0, 50.0));
+        let locked = network.add_node(square(200.0, 100.0, 50.0));
+        let other = network.add_node(square(400.0, 300.0, 50.0));
+        network.nodes[1].locked = true;
+        let position = |network: &Network, id: u32| network.node(id).unwrap().bounds.position();
+
+        network.select_all();
+        network.align_selected(AlignKind::Top);
+        network.flip_selection(Axis::Horizontal);
+        network.distribute_selected(Axis::Vertical);
+        assert_eq!(position(&network, locked), Point::new(200.0, 100.0));
+        assert_eq!(position(&network, other), Point::new(0.0, 0.0));
+
+        network.unselect_all_nodes();
+        for id in [free, locked] {
+            network.nodes.iter_mut().find(|node| node.id == id).unwrap().is_selected = true;
+        }
+        let group = network.group_selected().unwrap();
+        let anchor = network.node(free).unwrap().get_pos();
+        network.move_group(group, anchor + Vector::new(30.0, -20.0));
+        assert_eq!(network.node(free).unwrap().get_pos(), anchor + Vector::new(30.0, -20.0));
+        assert_eq!(position(&network, locked), Point::new(200.0, 100.0));
+        // The anchor keeps following the drag though the box now also
+        // spans the locked node.
+        network.move_group(group, anchor + Vector::new(60.0, -40.0));
+        assert_eq!(network.node(free).unwrap().get_pos(), anchor + Vector::new(60.0, -40.0));
+    }
+
+    #[test]
+    fn group_drags_leave_members_on_unavailable_layers_alone(){
+        let mut network = Network::import_json(r#"{
+            "nodes": [
+                {"id": 1, "x": 0, "y": 0, "layer": "Back"},
+                {"id": 2, "x": 200, "y": 0},
+                {"id": 3, "x": 400, "y": 0, "layer": "Hidden"}
+            ],
+            "groups": [{"id": 4, "label": "all", "member_ids": [1, 2, 3]}],
+            "layers": [{"name": "Back", "locked": true}, {"name": "Default"}, {"name": "Hidden", "visible": false}]
+        }"#).unwrap();
+        assert_eq!(network.group_anchor(4).map(Node::id), Some(2));
+        network.move_group(4, Vector::new(250.0, 50.0));
+        let positions: Vec<Point> = network.nodes.iter().map(|node| node.bounds.position()).collect();
+        assert_eq!(positions, [Point::new(0.0, 0.0), Point::new(250.0, 50.0), Point::new(400.0, 0.0)]);
+
+        network.set_layer_locked("Default", true);
+        assert!(network.group_anchor(4).is_none());
+        network.move_group(4, Vector::new(0.0, 0.0));
+        assert_eq!(network.node(2).unwrap().bounds.position(), Point::new(250.0, 50.0));
+    }
+
+    /// Two overlapping squares on different layers, the second drawn on
+    /// top; the overlap is around (75, 75).
+    const OVERLAPPING: &str = r#"{
+        "nodes": [
+            {"id": 1, "x": 0, "y": 0, "width": 100, "height": 100},
+            {"id": 2, "x": 50, "y": 50, "width": 100, "height": 100, "layer": "Top"}
+        ],
+        "layers": [{"name": "Default"}, {"name": "Top"}]
+    }"#;
+
+    #[test]
+    fn topmost_node_is_the_last_drawn_across_layers(){
+        let network = Network::import_json(OVERLAPPING).unwrap();
+        assert_eq!(network.topmost_node_at(Point::new(75.0, 75.0)), Some(2));
+        assert_eq!(network.topmost_node_at(Point::new(25.0, 25.0)), Some(1));
+        assert_eq!(network.topmost_node_at(Point::new(125.0, 125.0)), Some(2));
+        assert_eq!(network.topmost_node_at(Point::new(125.0, 25.0)), None);
+    }
+
+    #[test]
+    fn hidden_and_locked_layers_let_clicks_through(){
+        let mut network = Network::import_json(OVERLAPPING).unwrap();
+        network.set_layer_visible("Top", false);
+        assert_eq!(network.topmost_node_at(Point::new(75.0, 75.0)), Some(1));
+        assert_eq!(network.topmost_node_at(Point::new(125.0, 125.0)), None);
+
+        network.set_layer_visible("Top", true);
+        network.set_layer_locked("Top", true);
+        assert_eq!(network.topmost_node_at(Point::new(75.0, 75.0)), Some(1));
+        network.set_layer_locked("Top", false);
+        network.set_layer_locked(Layer::DEFAULT, true);
+        assert_eq!(network.topmost_node_at(Point::new(25.0, 25.0)), None);
+        assert_eq!(network.topmost_node_at(Point::new(75.0, 75.0)), Some(2));
+    }
+
+    #[test]
+    fn topmost_node_honors_the_shape(){
+        let mut network = Network::import_json(OVERLAPPING).unwrap();
+        let corner = Point::new(55.0, 55.0);
+        assert_eq!(network.topmost_node_at(corner), Some(2));
+        // The corner of the top node is outside its ellipse, so the node
+        // under it gets the click.
+        network.nodes[1].shape = NodeShape::Ellipse;
+        assert_eq!(network.topmost_node_at(corner), Some(1));
+    }
+
+    #[test]
+    fn node_alpha_ignores_values_that_are_not_finite(){
+        let mut network = Network::empty();
+        let id = network.add_node(square(0.0, 0.0, 50.0));
+        network.set_node_alpha(id, 0.5);
+        network.set_node_alpha(id, f32::NAN);
+        network.set_node_alpha(id, f32::INFINITY);
+        assert_eq!(network.node(id).unwrap().alpha(), 0.5);
+        network.set_node_alpha(id, 3.0);
+        assert_eq!(network.node(id).unwrap().alpha(), 1.0);
+    }
+
+    #[test]
+    fn node_bounds_must_be_finite_with_a_positive_size(){
+        let mut network = Network::empty();
+        let id = network.add_node(square(0.0, 0.0, 50.0));
+        let bounds = Rectangle{ x: 10.0, y: 20.0, width: 30.0, height: 40.0 };
+        network.set_node_bounds(id, bounds);
+        for bad in [
+            Rectangle{ x: f32::NAN, ..bounds },
+            Rectangle{ y: f32::NEG_INFINITY, ..bounds },
+            Rectangle{ width: f32::INFINITY, ..bounds },
+            Rectangle{ height: f32::NAN, ..bounds },
+            Rectangle{ width: 0.0, ..bounds },
+            Rectangle{ height: -5.0, ..bounds },
+        ] {
+            network.set_node_bounds(id, bad);
+        }
+        assert_eq!(network.node(id).unwrap().bounds, bounds);
+    }
+
+    #[test]
+    fn scaling_limits_that_make_no_sense_are_corrected(){
+        let network = Network::empty().with_scaling_limits(-1.0, f32::NAN);
+        assert_eq!((network.min_scaling, network.max_scaling), (Network::DEFAULT_MIN_SCALING, Network::DEFAULT_MAX_SCALING));
+        let network = Network::empty().with_scaling_limits(0.5, 0.25);
+        assert_eq!((network.min_scaling, network.max_scaling), (0.5, 0.5));
+        assert_eq!(network.camera().scaling, 0.5);
+    }
+
+    #[test]
+    fn inertia_pans_without_modifying_the_document(){
+        let mut network = Network::empty();
+        network.pan_velocity = Vector::new(500.0, 0.0);
+        let start = std::time::Instant::now();
+        network.tick(start);
+        network.tick(start + std::time::Duration::from_millis(16));
+        assert!(network.camera().translation.x > 0.0);
+        assert!(!network.is_dirty());
+        assert_eq!(network.revision(), 0);
+    }
+
+    #[test]
+    fn exported_image_shows_pinned_nodes_and_annotations(){
+        let mut network = Network::empty();
+        // Pinned nodes are placed in screen pixels.
+        let mut pinned = square(10.0, 10.0, 40.0);
+        pinned.pinned = true;
+        pinned.color = Some(iced::Color::from_rgb(1.0, 0.0, 0.0));
+        network.add_node(pinned);
+        network.start_stroke(Point::new(-50.0, 30.0));
+        network.extend_stroke(Point::new(50.0, 30.0));
+        network.finish_stroke();
+        // Twice the resolution of a 200 x 100 canvas.
+        let image = network.render_image(iced::Size::new(200.0, 100.0), 400, 200);
+        assert_eq!(image.get_pixel(60, 60).0, [255, 0, 0, 255]);
+        let background = image.get_pixel(300, 20).0;
+        // The stroke runs through the world point (0, 30), at canvas (100, 80).
+        assert_ne!(image.get_pixel(200, 160).0, background);
+    }
+
+    #[test]
+    fn dot_import_skips_refused_edges_and_fits_the_rest(){
+        let network = Network::import_dot("graph { a -- b; a -- b; b -- a; c -- c }").expect("the document is valid");
+        assert_eq!(network.nodes.len(), 3);
+        // One edge each way between a and b, drawn in lanes of their own.
+        assert_eq!(network.edges.len(), 2);
+        assert!(network.edges.iter().all(|edge| edge.lane != 0.0));
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use iced::{Rectangle, Size, Vector};
+
+use super::{Camera, Network};
+
+/// Nodes gliding from where they were to where a layout put them.
+pub(super) struct LayoutAnimation{
+    /// Node id, start position and target position.
+    moves: Vec<(u32, Vector, Vector)>,
+    elapsed: Duration,
+}
+
+/// The view gliding towards a node or an area, see `Network::focus_node`
+/// and `Network::fit_selection`.
+pub(super) struct CameraAnimation{
+    start: Camera,
+    target: Camera,
+    elapsed: Duration,
+}
+
+impl Network{
+    const LAYOUT_ANIMATION_DURATION: Duration = Duration::from_millis(400);
+
+    /// Enables or disables animating nodes to the positions chosen by the
+    /// layouts. When disabled, nodes jump there.
+    pub fn set_layout_animation(&mut self, enabled: bool){
+        self.animate_layouts = enabled;
+        if !enabled {
+            self.finish_layout_animation();
+        }
+    }
+
+    /// Moves nodes to the given top-left positions, gliding there over a
+    /// short time if layout animation is on. Replaces any animation in
+    /// progress, starting from where the nodes are now.
+    pub(super) fn move_nodes_to(&mut self, targets: Vec<(u32, Vector)>){
+        let moves: Vec<(u32, Vector, Vector)> = targets.into_iter()
+            .filter_map(|(id, target)| Some((id, self.node(id)?.get_pos(), target)))
+            .collect();
+        self.layout_animation = Some(LayoutAnimation{ moves, elapsed: Duration::ZERO });
+        if !self.animate_layouts {
+            self.finish_layout_animation();
+        }
+    }
+
+    /// Advances the layout animation by `elapsed`. Returns whether nodes
+    /// moved.
+    pub(super) fn tick_layout_animation(&mut self, elapsed: Duration) -> bool{
+        let animation = match self.layout_animation.as_mut() {
+            Some(animation) => animation,
+            None => return false,
+        };
+        animation.elapsed += elapsed;
+        let t = (animation.elapsed.as_secs_f32() / Self::LAYOUT_ANIMATION_DURATION.as_secs_f32()).min(1.0);
+        // Ease in and out, so nodes neither jump off nor slam into place.
+        let eased = if t < 0.5 {
+            4.0 * t * t * t
+        } else {
+            1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+        };
+        for (id, start, target) in &animation.moves {
+            if let Some(node) = self.nodes.iter_mut().find(|node| node.id == *id) {
+                node.set_new_pos(*start + (*target - *start) * eased);
+            }
+        }
+        if t >= 1.0 {
+            self.layout_animation = None;
+        }
+        self.mark_dirty();
+        true
+    }
+
+    /// Puts every animated node at its target right away.
+    fn finish_layout_animation(&mut self){
+        if let Some(animation) = self.layout_animation.take() {
+            for (id, _, target) in animation.moves {
+                if let Some(node) = self.nodes.iter_mut().find(|node| node.id == id) {
+                    node.set_new_pos(target);
+                }
+            }
+            self.mark_dirty();
+        }
+    }
+
+    /// Stops the layout animation, leaving the nodes where they are, e.g.
+    /// because the user grabbed one.
+    pub(super) fn stop_layout_animation(&mut self){
+        self.layout_animation = None;
+    }
+
+    pub(super) fn layout_animating(&self) -> bool{
+        self.layout_animation.is_some()
+    }
+}
+
+impl Network{
+    const CAMERA_ANIMATION_DURATION: Duration = Duration::from_millis(300);
+    /// Room left around the nodes when fitting them into the view, in
+    /// pixels.
+    const FIT_MARGIN: f32 = 40.0;
+
+    /// Glides the view so every visible node fits in a canvas of `size`.
+    pub fn fit_all(&mut self, size: Size){
+        let extent = Self::extent(self.nodes.iter().filter(|node| !node.pinned && !self.is_hidden(node)));
+        if let Some(extent) = extent {
+            self.glide_to_fit(extent, size);
+        }
+    }
+
+    /// Glides the view so the selected nodes fill a canvas of `size`, or
+    /// fits all nodes if none is selected.
+    pub fn fit_selection(&mut self, size: Size){
+        match Self::extent(self.nodes.iter().filter(|node| node.is_selected && !node.pinned)) {
+            Some(extent) => self.glide_to_fit(extent, size),
+            None => self.fit_all(size),
+        }
+    }
+
+    /// Glides the view until the world rectangle `area` is centered and
+    /// as large as fits in a canvas of `size`, within the zoom limits.
+    fn glide_to_fit(&mut self, area: Rectangle, size: Size){
+        let room = Size::new(
+            (size.width - 2.0 * Self::FIT_MARGIN).max(1.0),
+            (size.height - 2.0 * Self::FIT_MARGIN).max(1.0),
+        );
+        let scaling = (room.width / area.width.max(1.0))
+            .min(room.height / area.height.max(1.0))
+            .clamp(self.min_scaling, self.max_scaling);
+        let center = area.center();
+        self.camera_animation = Some(CameraAnimation{
+            start: self.camera(),
+            target: Camera{ translation: Vector::new(-center.x, -center.y), scaling },
+            elapsed: Duration::ZERO,
+        });
+    }
+
+    /// Glides the view until the node with the given id is centered,
+    /// zooming to `scaling` on the way if given. Any click or scroll stops
+    /// the move.
+    pub fn focus_node(&mut self, id: u32, scaling: Option<f32>){
+        let center = match self.node(id) {
+            Some(node) => node.bounds.center(),
+            None => return,
+        };
+        let scaling = scaling.unwrap_or(self.scaling).clamp(self.min_scaling, self.max_scaling);
+        self.camera_animation = Some(CameraAnimation{
+            start: self.camera(),
+            target: Camera{ translation: Vector::new(-center.x, -center.y), scaling },
+            elapsed: Duration::ZERO,
+        });
+    }
+
+    /// Advances the camera animation by `elapsed`. Returns whether the view
+    /// moved.
+    pub(super) fn tick_camera_animation(&mut self, elapsed: Duration) -> bool{
+        let animation = match self.camera_animation.as_mut() {
+            Some(animation) => animation,
+            None => return false,
+        };
+        animation.elapsed += elapsed;
+        let t = (animation.elapsed.as_secs_f32() / Self::CAMERA_ANIMATION_DURATION.as_secs_f32()).min(1.0);
+        // Ease out: start fast, settle gently on the node.
+        let eased = 1.0 - (1.0 - t).powi(3);
+        let (start, target) = (animation.start, animation.target);
+        self.translation = start.translation + (target.translation - start.translation) * eased;
+        self.scaling = start.scaling + (target.scaling - start.scaling) * eased;
+        self.clamp_translation();
+        if t >= 1.0 {
+            self.camera_animation = None;
+        }
+        self.request_redraw();
+        true
+    }
+
+    pub(super) fn stop_camera_animation(&mut self){
+        self.camera_animation = None;
+    }
+}
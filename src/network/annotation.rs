@@ -0,0 +1,180 @@
+use iced::{Color, Point, Rectangle, Vector};
+use iced_graphics::canvas::{Frame, LineCap, LineDash, LineJoin, Path, Stroke, Text};
+
+use super::{EditTarget, Interaction, LabelEdit, Network, Theme};
+
+/// Freehand stroke drawn over the graph with the pen tool.
+pub(super) struct Annotation{
+    /// World positions along the stroke, in drawing order.
+    pub(super) points: Vec<Point>,
+}
+
+impl Annotation{
+    pub(super) const WIDTH: f32 = 3.0;
+
+    /// Draws the stroke in world space; `scale` is the zoom level.
+    pub(super) fn draw(&self, frame: &mut Frame, color: Color, scale: f32){
+        let (first, rest) = match self.points.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        let path = Path::new(|builder| {
+            builder.move_to(*first);
+            for point in rest {
+                builder.line_to(*point);
+            }
+        });
+        frame.stroke(&path, Stroke{
+            color,
+            width: Self::WIDTH * scale,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+            ..Stroke::default()
+        });
+    }
+}
+
+impl Network{
+    /// Screen distance the cursor must move before the pen records another
+    /// point, keeping strokes from piling up points while the hand rests.
+    const PEN_STEP: f32 = 2.0;
+
+    /// Removes every annotation, freehand and text.
+    pub fn clear_annotations(&mut self){
+        if !self.annotations.is_empty() || !self.texts.is_empty() {
+            self.annotations.clear();
+            self.texts.clear();
+            if matches!(self.label_edit, Some(LabelEdit{ target: EditTarget::Text(_), .. })) {
+                self.label_edit = None;
+            }
+            self.mark_dirty();
+        }
+    }
+
+    /// Starts a stroke at the world position `world`.
+    pub(super) fn start_stroke(&mut self, world: Point){
+        self.annotations.push(Annotation{ points: vec![world] });
+        self.interaction = Interaction::Drawing;
+    }
+
+    /// Extends the stroke in progress to `world`.
+    pub(super) fn extend_stroke(&mut self, world: Point){
+        let step = Self::PEN_STEP / self.scaling;
+        if let Some(stroke) = self.annotations.last_mut() {
+            if stroke.points.last().is_none_or(|last| last.distance(world) >= step) {
+                stroke.points.push(world);
+            }
+        }
+    }
+
+    /// Keeps the stroke in progress. A click without moving leaves a dot.
+    pub(super) fn finish_stroke(&mut self){
+        if let Some(stroke) = self.annotations.last_mut() {
+            if let [point] = stroke.points[..] {
+                stroke.points.push(point + Vector::new(0.01, 0.0));
+            }
+        }
+        self.mark_dirty();
+    }
+
+    /// Drops the stroke in progress.
+    pub(super) fn discard_stroke(&mut self){
+        self.annotations.pop();
+    }
+
+    /// Handles a click at the world position `world` with the text tool:
+    /// edits the note under the cursor, or drops a new one there.
+    pub(super) fn place_text(&mut self, world: Point){
+        self.commit_label_edit();
+        let existing = self.texts.iter()
+            .rev()
+            .find(|text| text.bounds(&text.content).contains(world))
+            .map(|text| (text.id, text.content.clone()));
+        let (id, content) = match existing {
+            Some(existing) => existing,
+            None => {
+                let id = self.allocate_id();
+                self.texts.push(TextAnnotation{
+                    id,
+                    position: world,
+                    content: String::new(),
+                    size: TextAnnotation::DEFAULT_SIZE,
+                    color: None,
+                });
+                (id, String::new())
+            }
+        };
+        self.label_edit = Some(LabelEdit{ target: EditTarget::Text(id), text: content });
+        self.request_redraw();
+    }
+
+    /// Removes the notes left empty, e.g. after typing nothing into a new
+    /// one. The note being edited is kept.
+    pub(super) fn drop_empty_texts(&mut self){
+        let editing = self.label_edit.as_ref().map(|edit| edit.target);
+        self.texts.retain(|text| !text.content.is_empty() || editing == Some(EditTarget::Text(text.id)));
+    }
+
+    /// Draws the annotations above everything else in world space.
+    pub(super) fn draw_annotations(&self, frame: &mut Frame){
+        for annotation in &self.annotations {
+            annotation.draw(frame, self.theme.annotation, self.scaling);
+        }
+        for text in &self.texts {
+            text.draw(frame, self.edit_text(EditTarget::Text(text.id)), self.scaling, &self.theme);
+        }
+    }
+}
+
+/// Freestanding note placed with the text tool.
+pub(super) struct TextAnnotation{
+    pub(super) id: u32,
+    /// Top-left corner, in world coordinates.
+    pub(super) position: Point,
+    pub(super) content: String,
+    /// Font size in world units, so notes grow and shrink with the zoom.
+    pub(super) size: f32,
+    /// Text color, or `None` to use the theme's annotation color.
+    pub(super) color: Option<Color>,
+}
+
+impl TextAnnotation{
+    pub(super) const DEFAULT_SIZE: f32 = 18.0;
+
+    /// World area taken by the text. Glyph metrics aren't available here,
+    /// so the width is estimated; an empty note is one character wide.
+    pub(super) fn bounds(&self, content: &str) -> Rectangle{
+        let width = content.lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1) as f32 * self.size * 0.6;
+        let lines = content.lines().count().max(1) as f32;
+        Rectangle{ x: self.position.x, y: self.position.y, width, height: lines * self.size }
+    }
+
+    /// Draws the note in world space. `content` overrides the note's own
+    /// text while it is being edited, which also outlines it.
+    pub(super) fn draw(&self, frame: &mut Frame, content: Option<&str>, scale: f32, theme: &Theme){
+        let color = self.color.unwrap_or(theme.annotation);
+        let editing = content.is_some();
+        let content = content.unwrap_or(&self.content);
+        if editing {
+            let bounds = self.bounds(content);
+            let segments = [4.0 * scale, 3.0 * scale];
+            frame.stroke(&Path::rectangle(bounds.position(), bounds.size()), Stroke{
+                color,
+                width: scale,
+                line_dash: LineDash{ segments: &segments, offset: 0 },
+                ..Stroke::default()
+            });
+        }
+        frame.fill_text(Text{
+            content: content.to_string(),
+            position: self.position,
+            color,
+            size: self.size * scale,
+            ..Text::default()
+        });
+    }
+}
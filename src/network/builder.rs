@@ -0,0 +1,92 @@
+use iced::{Color, Point, Rectangle, Size};
+
+use super::{EdgeError, Network, Node};
+
+/// Builds a network from code, for embedders that don't load graphs from a
+/// file. Nodes and edges draw their ids from the same counter as the built
+/// network, so the ids returned here stay valid afterwards.
+pub struct NetworkBuilder{
+    network: Network,
+}
+
+impl NetworkBuilder{
+    pub fn new() -> Self{
+        NetworkBuilder{ network: Network::empty() }
+    }
+
+    /// Adds a node of the default size with its top-left corner at `pos`,
+    /// and returns its id. `None` uses the default node color.
+    pub fn add_node(&mut self, label: impl Into<String>, pos: Point, color: Option<Color>) -> u32{
+        let mut node = Node::with_bounds(Rectangle{
+            x: pos.x,
+            y: pos.y,
+            width: self.network.default_node_size.width,
+            height: self.network.default_node_size.height,
+        }).with_label(label);
+        node.color = color.or(self.network.default_node_color);
+        self.network.add_node(node)
+    }
+
+    /// Connects two nodes returned by `add_node` and returns the edge's id.
+    /// Fails like `Network::add_edge`.
+    pub fn add_edge(&mut self, from: u32, to: u32) -> Result<u32, EdgeError>{
+        self.network.add_edge(from, to)
+    }
+
+    /// Size of the nodes added after this; see
+    /// `Network::set_default_node_size`.
+    pub fn with_default_node_size(mut self, size: Size) -> Self{
+        self.network.set_default_node_size(size);
+        self
+    }
+
+    /// Fill of the nodes added after this without a color of their own;
+    /// see `Network::set_default_node_color`.
+    pub fn with_default_node_color(mut self, color: Option<Color>) -> Self{
+        self.network.set_default_node_color(color);
+        self
+    }
+
+    /// Initial zoom level, clamped to the network's scaling limits.
+    pub fn with_scaling(mut self, scaling: f32) -> Self{
+        self.network.scaling = scaling.clamp(self.network.min_scaling, self.network.max_scaling);
+        self
+    }
+
+    /// The built network, which starts out unmodified.
+    pub fn build(mut self) -> Network{
+        self.network.dirty = false;
+        self.network
+    }
+}
+
+impl Default for NetworkBuilder{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use iced::{Color, Point, Size};
+
+    use super::NetworkBuilder;
+
+    #[test]
+    fn builder_and_presets_use_the_default_node_size_and_color(){
+        let size = Size::new(60.0, 40.0);
+        let color = Color::from_rgb(0.5, 0.2, 0.9);
+        let mut builder = NetworkBuilder::new()
+            .with_default_node_size(size)
+            .with_default_node_color(Some(color));
+        let plain = builder.add_node("plain", Point::ORIGIN, None);
+        let red = builder.add_node("red", Point::new(100.0, 0.0), Some(Color::from_rgb(1.0, 0.0, 0.0)));
+        let network = builder.build();
+
+        let node = network.node(plain).unwrap();
+        assert_eq!((node.bounds().size(), node.color()), (size, Some(color)));
+        assert_eq!(network.node(red).unwrap().bounds().size(), size);
+        assert_eq!(network.node(red).unwrap().color(), Some(Color::from_rgb(1.0, 0.0, 0.0)));
+        assert_eq!(network.template_presets()[0].size, size);
+    }
+}
@@ -0,0 +1,211 @@
+use iced::{Point, Size, Vector};
+
+use super::Region;
+
+/// Where the view is centered and how far it is zoomed. Everything here is
+/// plain math on the two values, so it can be used without a window.
+///
+/// A world point `w` is drawn at `center + (w + translation) * scaling`,
+/// where `center` is the middle of the canvas. The world point in the
+/// middle is therefore `-translation` whatever the canvas size, so resizing
+/// the window grows or shrinks the view around the same point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera{
+    pub translation: Vector,
+    pub scaling: f32,
+}
+
+impl Camera{
+    /// World area shown in a canvas of `size`.
+    pub fn visible_region(&self, size: Size) -> Region{
+        let width = size.width / self.scaling;
+        let height = size.height / self.scaling;
+
+        Region {
+            x: -self.translation.x - width / 2.0,
+            y: -self.translation.y - height / 2.0,
+            width,
+            height,
+        }
+    }
+
+    /// Maps a position in a canvas of `size` to world space. This is the
+    /// inverse of `world_to_screen`, and of the transform `draw` applies:
+    /// center, then scale, then translate.
+    pub fn screen_to_world(&self, position: Point, size: Size) -> Point{
+        let region = self.visible_region(size);
+
+        Point::new(
+            position.x / self.scaling + region.x,
+            position.y / self.scaling + region.y,
+        )
+    }
+
+    /// Maps a world position to where it is drawn in a canvas of `size`.
+    pub fn world_to_screen(&self, world: Point, size: Size) -> Point{
+        Point::new(
+            size.width / 2.0 + (world.x + self.translation.x) * self.scaling,
+            size.height / 2.0 + (world.y + self.translation.y) * self.scaling,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use iced::{Point, Rectangle, Size, Vector};
+
+    use super::Camera;
+    use crate::network::{Network, Node};
+
+    const SIZE: Size = Size::new(800.0, 600.0);
+    const DEFAULT: Camera = Camera{ translation: Vector::new(0.0, 0.0), scaling: 1.0 };
+    const PANNED_AND_ZOOMED: Camera = Camera{ translation: Vector::new(-150.0, 40.0), scaling: 2.0 };
+
+    fn assert_close(actual: Point, expected: Point){
+        assert!(
+            (actual.x - expected.x).abs() < 1e-3 && (actual.y - expected.y).abs() < 1e-3,
+            "expected {:?}, got {:?}", expected, actual,
+        );
+    }
+
+    /// Where `Network::draw` puts a world point: it translates to the
+    /// center of the canvas, scales, then applies the translation.
+    fn drawn_at(camera: Camera, world: Point, size: Size) -> Point{
+        let center = Vector::new(size.width / 2.0, size.height / 2.0);
+        Point::ORIGIN + center + (Vector::new(world.x, world.y) + camera.translation) * camera.scaling
+    }
+
+    fn world_points() -> [Point; 4]{
+        [Point::ORIGIN, Point::new(150.0, -40.0), Point::new(-320.5, 87.25), Point::new(1000.0, 1000.0)]
+    }
+
+    #[test]
+    fn default_view_is_centered_on_the_origin(){
+        let region = DEFAULT.visible_region(SIZE);
+        assert_eq!((region.x, region.y, region.width, region.height), (-400.0, -300.0, 800.0, 600.0));
+        assert_close(DEFAULT.screen_to_world(Point::new(400.0, 300.0), SIZE), Point::ORIGIN);
+        assert_close(DEFAULT.screen_to_world(Point::ORIGIN, SIZE), Point::new(-400.0, -300.0));
+    }
+
+    #[test]
+    fn panned_and_zoomed_view_shows_the_translated_region(){
+        let region = PANNED_AND_ZOOMED.visible_region(SIZE);
+        assert_eq!((region.x, region.y, region.width, region.height), (-50.0, -190.0, 400.0, 300.0));
+        assert_close(PANNED_AND_ZOOMED.screen_to_world(Point::new(400.0, 300.0), SIZE), Point::new(150.0, -40.0));
+        assert_close(PANNED_AND_ZOOMED.screen_to_world(Point::new(800.0, 600.0), SIZE), Point::new(350.0, 110.0));
+    }
+
+    #[test]
+    fn screen_to_world_inverts_the_draw_transform(){
+        for camera in [DEFAULT, PANNED_AND_ZOOMED, Camera{ translation: Vector::new(12.5, -7.0), scaling: 0.3 }] {
+            for world in world_points() {
+                let screen = drawn_at(camera, world, SIZE);
+                assert_close(camera.world_to_screen(world, SIZE), screen);
+                assert_close(camera.screen_to_world(screen, SIZE), world);
+            }
+        }
+    }
+
+    #[test]
+    fn visible_region_is_what_the_corners_map_to(){
+        let region = PANNED_AND_ZOOMED.visible_region(SIZE);
+        assert_close(PANNED_AND_ZOOMED.screen_to_world(Point::ORIGIN, SIZE), Point::new(region.x, region.y));
+        let far_corner = PANNED_AND_ZOOMED.screen_to_world(Point::new(SIZE.width, SIZE.height), SIZE);
+        assert_close(far_corner, Point::new(region.x + region.width, region.y + region.height));
+    }
+
+    #[test]
+    fn network_maps_through_its_camera(){
+        let mut network = Network::empty();
+        network.set_camera(PANNED_AND_ZOOMED);
+        for world in world_points() {
+            let screen = drawn_at(PANNED_AND_ZOOMED, world, SIZE);
+            assert_close(network.world_to_screen(world, SIZE), screen);
+            assert_close(network.screen_to_world(screen, SIZE), world);
+        }
+    }
+
+    #[test]
+    fn nodes_are_drawn_at_known_screen_positions(){
+        let mut network = Network::empty();
+        let centered = network.add_node(Node::with_bounds(Rectangle{ x: 150.0, y: -40.0, width: 50.0, height: 50.0 }));
+        let origin = network.add_node(Node::with_bounds(Rectangle{ x: 0.0, y: 0.0, width: 50.0, height: 50.0 }));
+        network.set_camera(PANNED_AND_ZOOMED);
+        // The view is centered on (150, -40) at twice the size.
+        let expected = [
+            (centered, Point::new(400.0, 300.0), Point::new(500.0, 400.0)),
+            (origin, Point::new(100.0, 380.0), Point::new(200.0, 480.0)),
+        ];
+        for (id, top_left, bottom_right) in expected {
+            let bounds = network.node(id).map(Node::bounds).unwrap();
+            let far_corner = Point::new(bounds.x + bounds.width, bounds.y + bounds.height);
+            assert_close(drawn_at(PANNED_AND_ZOOMED, Point::new(bounds.x, bounds.y), SIZE), top_left);
+            assert_close(drawn_at(PANNED_AND_ZOOMED, far_corner, SIZE), bottom_right);
+            assert_close(network.world_to_screen(Point::new(bounds.x, bounds.y), SIZE), top_left);
+        }
+    }
+
+    #[test]
+    fn drawn_nodes_are_the_ones_intersecting_the_region(){
+        let mut network = Network::empty();
+        for row in -10..10 {
+            for column in -10..10 {
+                let bounds = Rectangle{ x: column as f32 * 70.0, y: row as f32 * 55.0, width: 40.0, height: 30.0 };
+                network.add_node(Node::with_bounds(bounds));
+            }
+        }
+        network.set_camera(PANNED_AND_ZOOMED);
+        let region = network.visible_region(SIZE);
+        let margin = Network::CULL_MARGIN / PANNED_AND_ZOOMED.scaling;
+        let expected: Vec<u32> = network.nodes.iter()
+            .filter(|node| {
+                let bounds = node.bounds();
+                bounds.x <= region.x() + region.width() + margin && bounds.x + bounds.width >= region.x() - margin
+                    && bounds.y <= region.y() + region.height() + margin && bounds.y + bounds.height >= region.y() - margin
+            })
+            .map(Node::id)
+            .collect();
+        let drawn: Vec<u32> = network.drawn_nodes(SIZE).map(Node::id).collect();
+        assert!(!drawn.is_empty() && drawn.len() < network.nodes.len());
+        assert_eq!(drawn, expected);
+    }
+
+    /// Network with a 100 by 100 node at the world origin and another one
+    /// to its right.
+    fn two_nodes() -> (Network, u32, u32){
+        let mut network = Network::empty();
+        let near = network.add_node(Node::with_bounds(Rectangle{ x: 0.0, y: 0.0, width: 100.0, height: 100.0 }));
+        let far = network.add_node(Node::with_bounds(Rectangle{ x: 300.0, y: 0.0, width: 100.0, height: 100.0 }));
+        (network, near, far)
+    }
+
+    #[test]
+    fn nodes_are_hit_where_they_are_drawn_after_panning(){
+        let (mut network, near, far) = two_nodes();
+        network.set_view_state(Vector::new(-300.0, 0.0), 1.0);
+        assert_eq!(network.get_node_at_screen(Point::new(450.0, 350.0), SIZE), Some(far));
+        assert_eq!(network.get_node_at_screen(Point::new(150.0, 350.0), SIZE), Some(near));
+        assert_eq!(network.get_node_at_screen(Point::new(350.0, 350.0), SIZE), None);
+    }
+
+    #[test]
+    fn nodes_are_hit_where_they_are_drawn_after_zooming(){
+        let (mut network, near, far) = two_nodes();
+        network.set_view_state(Vector::new(0.0, 0.0), 2.0);
+        // The near node now covers 400..600 across; the far one is off to
+        // the right, at 1000..1200.
+        assert_eq!(network.get_node_at_screen(Point::new(590.0, 490.0), SIZE), Some(near));
+        assert_eq!(network.get_node_at_screen(Point::new(610.0, 350.0), SIZE), None);
+
+        network.set_view_state(Vector::new(0.0, 0.0), 0.5);
+        // Now at 400..450 and 550..600.
+        assert_eq!(network.get_node_at_screen(Point::new(440.0, 340.0), SIZE), Some(near));
+        assert_eq!(network.get_node_at_screen(Point::new(575.0, 325.0), SIZE), Some(far));
+        assert_eq!(network.get_node_at_screen(Point::new(500.0, 325.0), SIZE), None);
+        for id in [near, far] {
+            let center = network.node(id).map(|node| node.bounds().center()).unwrap();
+            let screen = network.world_to_screen(center, SIZE);
+            assert_eq!(network.get_node_at_screen(screen, SIZE), Some(id));
+        }
+    }
+}
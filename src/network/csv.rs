@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::Network;
+
+impl Network{
+    /// Serializes the graph as a square adjacency matrix in CSV. Rows and
+    /// columns follow node ids in ascending order and are headed by the
+    /// node's label, or its id if it has none. A cell is 1 when an edge
+    /// leads from the row's node to the column's node. Unless `directed`,
+    /// edges count both ways and the matrix is symmetric.
+    pub fn export_adjacency_csv(&self, directed: bool) -> String{
+        let mut nodes: Vec<_> = self.nodes.iter().collect();
+        nodes.sort_by_key(|node| node.id);
+        let index: HashMap<u32, usize> = nodes.iter()
+            .enumerate()
+            .map(|(i, node)| (node.id, i))
+            .collect();
+        let mut matrix = vec![vec![false; nodes.len()]; nodes.len()];
+        for edge in &self.edges {
+            if let (Some(&from), Some(&to)) = (index.get(&edge.from), index.get(&edge.to)) {
+                matrix[from][to] = true;
+                if !directed {
+                    matrix[to][from] = true;
+                }
+            }
+        }
+
+        let headers: Vec<String> = nodes.iter()
+            .map(|node| if node.label.is_empty() {
+                node.id.to_string()
+            } else {
+                escape(&node.label)
+            })
+            .collect();
+        let mut out = String::new();
+        for header in &headers {
+            out.push(',');
+            out.push_str(header);
+        }
+        out.push('\n');
+        for (header, row) in headers.iter().zip(matrix) {
+            out.push_str(header);
+            for cell in row {
+                let _ = write!(out, ",{}", cell as u8);
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn escape(field: &str) -> String{
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use iced::{alignment, Color, Point, Rectangle, Size, Vector};
+use iced_graphics::canvas::{Frame, Path, Text};
+
+use super::Network;
+
+/// Pointer of another user editing the same network.
+pub(super) struct RemoteCursor{
+    user: String,
+    /// World position.
+    position: Point,
+    /// Time since the last update, advanced by `Network::tick`.
+    age: Duration,
+}
+
+impl RemoteCursor{
+    /// How long a cursor stays fully visible without updates.
+    const STALE_AFTER: Duration = Duration::from_secs(5);
+    /// How long a stale cursor takes to fade out before it is dropped.
+    const FADE_DURATION: Duration = Duration::from_secs(2);
+    const LABEL_SIZE: f32 = 14.0;
+
+    /// 1 while the cursor is fresh, then down to 0 as it fades.
+    fn opacity(&self) -> f32{
+        match self.age.checked_sub(Self::STALE_AFTER) {
+            Some(stale) => 1.0 - stale.as_secs_f32() / Self::FADE_DURATION.as_secs_f32(),
+            None => 1.0,
+        }
+    }
+
+    /// Color of the user, the same on every update.
+    fn color(&self) -> Color{
+        let hash = self.user.bytes().fold(0usize, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as usize));
+        Network::COMPONENT_PALETTE[hash % Network::COMPONENT_PALETTE.len()]
+    }
+
+    /// Draws an arrow pointer with its tip at `tip`, in screen space, and
+    /// the user's name next to it.
+    fn draw(&self, frame: &mut Frame, tip: Point, backdrop: Color){
+        let alpha = self.opacity().clamp(0.0, 1.0);
+        let color = Color{ a: alpha, ..self.color() };
+        let pointer = Path::new(|p| {
+            p.move_to(tip);
+            p.line_to(tip + Vector::new(0.0, 16.0));
+            p.line_to(tip + Vector::new(4.5, 12.0));
+            p.line_to(tip + Vector::new(11.0, 12.0));
+            p.close();
+        });
+        frame.fill(&pointer, color);
+
+        let position = tip + Vector::new(12.0, 14.0);
+        let padding = 3.0;
+        // Glyph metrics aren't available here, so estimate the width.
+        let width = self.user.chars().count() as f32 * Self::LABEL_SIZE * 0.6;
+        let background = Path::rectangle(
+            position - Vector::new(padding, padding),
+            Size::new(width + 2.0 * padding, Self::LABEL_SIZE + 2.0 * padding),
+        );
+        frame.fill(&background, Color{ a: backdrop.a * alpha, ..backdrop });
+        frame.fill_text(Text{
+            content: self.user.clone(),
+            position,
+            color,
+            size: Self::LABEL_SIZE,
+            horizontal_alignment: alignment::Horizontal::Left,
+            vertical_alignment: alignment::Vertical::Top,
+            ..Text::default()
+        });
+    }
+}
+
+impl Network{
+    /// Shows another user's cursor at a world position, or moves it there.
+    /// Cursors that aren't updated for a few seconds fade out and are
+    /// dropped, as `tick` is called.
+    pub fn set_remote_cursor(&mut self, user: String, world_pos: Point){
+        match self.remote_cursors.iter_mut().find(|cursor| cursor.user == user) {
+            Some(cursor) => {
+                cursor.position = world_pos;
+                cursor.age = Duration::ZERO;
+            }
+            None => self.remote_cursors.push(RemoteCursor{ user, position: world_pos, age: Duration::ZERO }),
+        }
+    }
+
+    /// Hides another user's cursor right away, e.g. when they leave.
+    pub fn remove_remote_cursor(&mut self, user: &str){
+        self.remote_cursors.retain(|cursor| cursor.user != user);
+    }
+
+    /// Ages the remote cursors and drops the ones that have faded out.
+    pub(super) fn tick_remote_cursors(&mut self, elapsed: Duration){
+        let lifetime = RemoteCursor::STALE_AFTER + RemoteCursor::FADE_DURATION;
+        for cursor in self.remote_cursors.iter_mut() {
+            cursor.age += elapsed;
+        }
+        self.remote_cursors.retain(|cursor| cursor.age < lifetime);
+    }
+
+    /// Draws the remote cursors in screen space, where their world
+    /// positions show in the current view.
+    pub(super) fn draw_remote_cursors(&self, frame: &mut Frame, bounds: Rectangle){
+        let backdrop = self.theme.backdrop();
+        for cursor in &self.remote_cursors {
+            cursor.draw(frame, self.world_to_screen(cursor.position, bounds.size()), backdrop);
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use std::fmt;
+
+/// Error produced when a DOT document cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError{
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError{}
+
+/// Nodes and edges of a DOT graph, in order of first appearance. Edges
+/// refer to nodes by index into `nodes`.
+pub struct DotGraph{
+    pub nodes: Vec<String>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token{
+    Id(String),
+    Arrow,
+    Line,
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Semicolon,
+    Comma,
+    Equals,
+    Colon,
+}
+
+impl fmt::Display for Token{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Id(id) => write!(f, "'{}'", id),
+            Token::Arrow => write!(f, "'->'"),
+            Token::Line => write!(f, "'--'"),
+            Token::OpenBrace => write!(f, "'{{'"),
+            Token::CloseBrace => write!(f, "'}}'"),
+            Token::OpenBracket => write!(f, "'['"),
+            Token::CloseBracket => write!(f, "']'"),
+            Token::Semicolon => write!(f, "';'"),
+            Token::Comma => write!(f, "','"),
+            Token::Equals => write!(f, "'='"),
+            Token::Colon => write!(f, "':'"),
+        }
+    }
+}
+
+fn tokenize(src: &str) -> Result<Vec<(Token, usize)>, ParseError>{
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '#' => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let start = line;
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            previous = c;
+                        }
+                        None => return Err(ParseError{
+                            line: start,
+                            message: String::from("unterminated comment"),
+                        }),
+                    }
+                }
+                continue;
+            }
+            '{' => Token::OpenBrace,
+            '}' => Token::CloseBrace,
+            '[' => Token::OpenBracket,
+            ']' => Token::CloseBracket,
+            ';' => Token::Semicolon,
+            ',' => Token::Comma,
+            '=' => Token::Equals,
+            ':' => Token::Colon,
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                Token::Arrow
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                chars.next();
+                Token::Line
+            }
+            '"' => {
+                let start = line;
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            id.push('"');
+                        }
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            id.push(c);
+                        }
+                        None => return Err(ParseError{
+                            line: start,
+                            message: String::from("unterminated string"),
+                        }),
+                    }
+                }
+                Token::Id(id)
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || next == '_' || next == '.' {
+                        id.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Id(id)
+            }
+            c => return Err(ParseError{
+                line,
+                message: format!("unexpected character '{}'", c),
+            }),
+        };
+        tokens.push((token, line));
+    }
+    Ok(tokens)
+}
+
+struct Parser{
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    directed: bool,
+    graph: DotGraph,
+}
+
+impl Parser{
+    fn peek(&self) -> Option<&Token>{
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize{
+        self.tokens.get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error(&self, message: String) -> ParseError{
+        ParseError{ line: self.line(), message }
+    }
+
+    fn next(&mut self) -> Result<Token, ParseError>{
+        let token = self.tokens.get(self.position)
+            .map(|(token, _)| token.clone())
+            .ok_or_else(|| self.error(String::from("unexpected end of input")))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError>{
+        let token = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            self.position -= 1;
+            Err(self.error(format!("expected {}, found {}", expected, token)))
+        }
+    }
+
+    fn id(&mut self) -> Result<String, ParseError>{
+        match self.next()? {
+            Token::Id(id) => Ok(id),
+            token => {
+                self.position -= 1;
+                Err(self.error(format!("expected an identifier, found {}", token)))
+            }
+        }
+    }
+
+    fn node_index(&mut self, id: String) -> usize{
+        match self.graph.nodes.iter().position(|node| *node == id) {
+            Some(index) => index,
+            None => {
+                self.graph.nodes.push(id);
+                self.graph.nodes.len() - 1
+            }
+        }
+    }
+
+    fn graph(&mut self) -> Result<(), ParseError>{
+        let mut keyword = self.id()?;
+        if keyword.eq_ignore_ascii_case("strict") {
+            keyword = self.id()?;
+        }
+        self.directed = if keyword.eq_ignore_ascii_case("digraph") {
+            true
+        } else if keyword.eq_ignore_ascii_case("graph") {
+            false
+        } else {
+            self.position -= 1;
+            return Err(self.error(format!("expected 'graph' or 'digraph', found '{}'", keyword)));
+        };
+        if let Some(Token::Id(_)) = self.peek() {
+            self.position += 1;
+        }
+        self.expect(Token::OpenBrace)?;
+        while self.peek() != Some(&Token::CloseBrace) {
+            if self.peek().is_none() {
+                return Err(self.error(String::from("missing closing '}'")));
+            }
+            self.statement()?;
+        }
+        self.expect(Token::CloseBrace)?;
+        if let Some(token) = self.peek() {
+            return Err(self.error(format!("unexpected {} after the end of the graph", token)));
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self) -> Result<(), ParseError>{
+        if self.peek() == Some(&Token::Semicolon) {
+            self.position += 1;
+            return Ok(());
+        }
+        if self.peek() == Some(&Token::OpenBrace) {
+            return Err(self.error(String::from("subgraphs are not supported")));
+        }
+        let id = self.id()?;
+        let is_keyword = |keyword: &str| id.eq_ignore_ascii_case(keyword);
+        if is_keyword("subgraph") {
+            self.position -= 1;
+            return Err(self.error(String::from("subgraphs are not supported")));
+        }
+        if is_keyword("graph") || is_keyword("node") || is_keyword("edge") {
+            self.attributes()?;
+            return self.end_statement();
+        }
+        if self.peek() == Some(&Token::Equals) {
+            self.position += 1;
+            self.id()?;
+            return self.end_statement();
+        }
+
+        self.port()?;
+        let mut from = self.node_index(id);
+        loop {
+            match self.peek() {
+                Some(Token::Arrow) if !self.directed => {
+                    return Err(self.error(String::from("'->' used in an undirected graph")));
+                }
+                Some(Token::Line) if self.directed => {
+                    return Err(self.error(String::from("'--' used in a directed graph")));
+                }
+                Some(Token::Arrow) | Some(Token::Line) => {
+                    self.position += 1;
+                    let id = self.id()?;
+                    self.port()?;
+                    let to = self.node_index(id);
+                    self.graph.edges.push((from, to));
+                    from = to;
+                }
+                _ => break,
+            }
+        }
+        self.attributes()?;
+        self.end_statement()
+    }
+
+    /// Skips an optional `:port[:compass]` suffix on a node id.
+    fn port(&mut self) -> Result<(), ParseError>{
+        for _ in 0..2 {
+            if self.peek() == Some(&Token::Colon) {
+                self.position += 1;
+                self.id()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips any number of `[key = value, ...]` attribute lists.
+    fn attributes(&mut self) -> Result<(), ParseError>{
+        while self.peek() == Some(&Token::OpenBracket) {
+            self.position += 1;
+            while self.peek() != Some(&Token::CloseBracket) {
+                self.id()?;
+                self.expect(Token::Equals)?;
+                self.id()?;
+                if let Some(Token::Comma) | Some(Token::Semicolon) = self.peek() {
+                    self.position += 1;
+                }
+            }
+            self.expect(Token::CloseBracket)?;
+        }
+        Ok(())
+    }
+
+    fn end_statement(&mut self) -> Result<(), ParseError>{
+        if self.peek() == Some(&Token::Semicolon) {
+            self.position += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the nodes and edges of a DOT document. Attributes are accepted
+/// but ignored.
+pub fn parse(src: &str) -> Result<DotGraph, ParseError>{
+    let mut parser = Parser{
+        tokens: tokenize(src)?,
+        position: 0,
+        directed: true,
+        graph: DotGraph{ nodes: Vec::new(), edges: Vec::new() },
+    };
+    parser.graph()?;
+    Ok(parser.graph)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::{parse, ParseError};
+
+    fn parse_error(src: &str) -> ParseError{
+        match parse(src) {
+            Ok(_) => panic!("{:?} should not parse", src),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn simple_digraph(){
+        let graph = parse("digraph G {\n  a -> b;\n  b -> c -> a\n  d\n}").expect("the document is valid");
+        assert_eq!(graph.nodes, ["a", "b", "c", "d"]);
+        assert_eq!(graph.edges, [(0, 1), (1, 2), (2, 0)]);
+    }
+
+    #[test]
+    fn attributes_are_ignored(){
+        let graph = parse(r#"digraph {
+            graph [rankdir=LR];
+            node [shape=box, color="red"];
+            a [label="First"; style=filled]
+            a -> b [weight=2] [color=blue];
+            rankdir = TB
+        }"#).expect("the document is valid");
+        assert_eq!(graph.nodes, ["a", "b"]);
+        assert_eq!(graph.edges, [(0, 1)]);
+    }
+
+    #[test]
+    fn missing_closing_brace(){
+        let error = parse_error("digraph {\n  a -> b;\n");
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "missing closing '}'");
+    }
+
+    #[test]
+    fn unterminated_statement(){
+        let error = parse_error("digraph {\n  a -> \n}");
+        assert_eq!(error, ParseError{ line: 3, message: String::from("expected an identifier, found '}'") });
+        let error = parse_error("digraph { a [color=red }");
+        assert_eq!(error.message, "expected an identifier, found '}'");
+    }
+
+    #[test]
+    fn unknown_token(){
+        let error = parse_error("digraph {\n  a -> b;\n  b @ c\n}");
+        assert_eq!(error, ParseError{ line: 3, message: String::from("unexpected character '@'") });
+    }
+
+    #[test]
+    fn unterminated_string_and_comment(){
+        assert_eq!(parse_error("digraph {\n  \"a -> b\n}").message, "unterminated string");
+        assert_eq!(parse_error("digraph { a /* b -> c }").message, "unterminated comment");
+    }
+}
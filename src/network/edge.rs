@@ -0,0 +1,583 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use iced::{alignment, Color, Point, Rectangle, Size, Vector};
+use iced_graphics::canvas::{Frame, LineCap, LineDash, Path, Stroke, Text};
+use serde::{Deserialize, Serialize};
+
+use super::{Layer, Network, Theme};
+use super::geometry::{DrawKey, GeometryCache};
+
+/// How edges are routed between their nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeStyle{
+    #[default]
+    Straight,
+    /// Quadratic bezier bowed to the side of the straight line.
+    Curved,
+    /// Horizontal and vertical segments between facing sides of the nodes.
+    Orthogonal,
+}
+
+impl EdgeStyle{
+    /// The style that follows this one when cycling through styles.
+    pub fn next(self) -> Self{
+        match self {
+            EdgeStyle::Straight => EdgeStyle::Curved,
+            EdgeStyle::Curved => EdgeStyle::Orthogonal,
+            EdgeStyle::Orthogonal => EdgeStyle::Straight,
+        }
+    }
+}
+
+/// Dash pattern an edge is stroked with, e.g. to tell kinds of edges
+/// apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineStyle{
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl LineStyle{
+    /// The style that follows this one when cycling through styles.
+    pub fn next(self) -> Self{
+        match self {
+            LineStyle::Solid => LineStyle::Dashed,
+            LineStyle::Dashed => LineStyle::Dotted,
+            LineStyle::Dotted => LineStyle::Solid,
+        }
+    }
+
+    pub fn name(self) -> &'static str{
+        match self {
+            LineStyle::Solid => "solid",
+            LineStyle::Dashed => "dashed",
+            LineStyle::Dotted => "dotted",
+        }
+    }
+
+    /// Lengths of the dashes and gaps for a line of the given width. Dots
+    /// are very short dashes drawn with round caps.
+    fn segments(self, width: f32) -> Option<[f32; 2]>{
+        match self {
+            LineStyle::Solid => None,
+            LineStyle::Dashed => Some([3.0 * width, 2.0 * width]),
+            LineStyle::Dotted => Some([0.1 * width, 2.0 * width]),
+        }
+    }
+}
+
+/// Why `Network::add_edge` refused to connect two nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeError{
+    /// Both ends are the same node.
+    SelfLoop(u32),
+    /// An edge with the same source and target exists already; holds its id.
+    Duplicate(u32),
+    UnknownNode(u32),
+    UnknownEdge(u32),
+}
+
+impl fmt::Display for EdgeError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdgeError::SelfLoop(id) => write!(f, "node {} can't be connected to itself", id),
+            EdgeError::Duplicate(id) => write!(f, "edge {} already connects these nodes", id),
+            EdgeError::UnknownNode(id) => write!(f, "there is no node {}", id),
+            EdgeError::UnknownEdge(id) => write!(f, "there is no edge {}", id),
+        }
+    }
+}
+
+impl std::error::Error for EdgeError{}
+
+pub(super) struct Edge{
+    pub(super) id: u32,
+    pub(super) from: u32,
+    pub(super) to: u32,
+    pub(super) from_port: Option<String>,
+    pub(super) to_port: Option<String>,
+    pub(super) label: Option<String>,
+    pub(super) is_selected: bool,
+    /// Strength of the connection, e.g. traffic or similarity.
+    pub(super) weight: Option<f32>,
+    /// Stroke width relative to the default, kept in sync with the weights
+    /// by `Network::fit_edge_widths`.
+    pub(super) width: f32,
+    pub(super) line_dash: LineStyle,
+    /// Name of the layer the edge is on.
+    pub(super) layer: String,
+    /// Lane among the edges between the same two nodes, kept in sync by
+    /// `Network::fit_parallel_edges`: 0 for a lone edge, otherwise spread
+    /// evenly around 0. Signed as seen from the lower node id, so edges in
+    /// opposite directions share one numbering.
+    pub(super) lane: f32,
+    pub(super) cache: GeometryCache,
+}
+
+/// World-space course of an edge: it starts at `from`, optionally bends on
+/// the way, and ends on the border of the target node at `to`.
+pub(super) struct EdgeRoute{
+    pub(super) from: Point,
+    pub(super) to: Point,
+    pub(super) bend: Bend,
+}
+
+pub(super) enum Bend{
+    Straight,
+    /// Quadratic curve towards the control point.
+    Curve(Point),
+    /// Straight segments through the corners, in order.
+    Corners(Vec<Point>),
+}
+
+impl EdgeRoute{
+    /// Offset of the curve's control point from the midpoint of the straight
+    /// line, relative to the line's length.
+    pub(super) const CURVATURE: f32 = 0.2;
+    /// Extra curvature between neighboring lanes of parallel edges.
+    pub(super) const LANE_CURVATURE: f32 = 0.25;
+    const CURVE_SEGMENTS: usize = 24;
+
+    /// Route made of horizontal and vertical segments from `source` to
+    /// `target`. It leaves and enters through the sides facing each other
+    /// along the axis the boxes are farthest apart on, with a Z-shaped
+    /// detour halfway; boxes overlapping along that axis get an L-shape
+    /// entering the target from the other axis. Ports replace the sides
+    /// as end points.
+    pub(super) fn orthogonal(source: Rectangle, target: Rectangle, from_port: Option<Point>, to_port: Option<Point>) -> Self{
+        let (start, end) = (source.center(), target.center());
+        let delta = end - start;
+        let horizontal = delta.x.abs() >= delta.y.abs();
+        let (exit, entry) = if horizontal {
+            let (exit, entry) = if delta.x >= 0.0 {
+                (source.x + source.width, target.x)
+            } else {
+                (source.x, target.x + target.width)
+            };
+            (Point::new(exit, start.y), Point::new(entry, end.y))
+        } else {
+            let (exit, entry) = if delta.y >= 0.0 {
+                (source.y + source.height, target.y)
+            } else {
+                (source.y, target.y + target.height)
+            };
+            (Point::new(start.x, exit), Point::new(end.x, entry))
+        };
+        let from = from_port.unwrap_or(exit);
+        let gap = if horizontal {
+            (entry.x - from.x) * delta.x.signum()
+        } else {
+            (entry.y - from.y) * delta.y.signum()
+        };
+
+        let (to, mut corners) = if gap >= 0.0 || to_port.is_some() {
+            let to = to_port.unwrap_or(entry);
+            let corners = if horizontal {
+                let x = (from.x + to.x) / 2.0;
+                vec![Point::new(x, from.y), Point::new(x, to.y)]
+            } else {
+                let y = (from.y + to.y) / 2.0;
+                vec![Point::new(from.x, y), Point::new(to.x, y)]
+            };
+            (to, corners)
+        } else if horizontal {
+            let y = if delta.y >= 0.0 { target.y } else { target.y + target.height };
+            (Point::new(end.x, y), vec![Point::new(end.x, from.y)])
+        } else {
+            let x = if delta.x >= 0.0 { target.x } else { target.x + target.width };
+            (Point::new(x, end.y), vec![Point::new(from.x, end.y)])
+        };
+        corners.dedup();
+        corners.retain(|corner| *corner != from && *corner != to);
+        EdgeRoute{ from, to, bend: Bend::Corners(corners) }
+    }
+
+    pub(super) fn path(&self) -> Path{
+        Path::new(|builder| {
+            builder.move_to(self.from);
+            match &self.bend {
+                Bend::Straight => builder.line_to(self.to),
+                Bend::Curve(control) => builder.quadratic_curve_to(*control, self.to),
+                Bend::Corners(corners) => {
+                    for corner in corners {
+                        builder.line_to(*corner);
+                    }
+                    builder.line_to(self.to);
+                }
+            }
+        })
+    }
+
+    /// Axis-aligned box containing the whole route. A quadratic curve stays
+    /// within the triangle of its end and control points.
+    pub(super) fn bounds(&self) -> Rectangle{
+        let mut points = vec![self.from, self.to];
+        match &self.bend {
+            Bend::Straight => {}
+            Bend::Curve(control) => points.push(*control),
+            Bend::Corners(corners) => points.extend(corners),
+        }
+        let points = points.iter();
+        let min_x = points.clone().map(|p| p.x).fold(f32::INFINITY, f32::min);
+        let min_y = points.clone().map(|p| p.y).fold(f32::INFINITY, f32::min);
+        let max_x = points.clone().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max);
+        let max_y = points.map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+        Rectangle{ x: min_x, y: min_y, width: max_x - min_x, height: max_y - min_y }
+    }
+
+    /// The route flattened into line segments.
+    pub(super) fn polyline(&self) -> Vec<Point>{
+        match &self.bend {
+            Bend::Straight => vec![self.from, self.to],
+            Bend::Curve(control) => (0..=Self::CURVE_SEGMENTS)
+                .map(|i| {
+                    let t = i as f32 / Self::CURVE_SEGMENTS as f32;
+                    let u = 1.0 - t;
+                    Point::new(
+                        u * u * self.from.x + 2.0 * u * t * control.x + t * t * self.to.x,
+                        u * u * self.from.y + 2.0 * u * t * control.y + t * t * self.to.y,
+                    )
+                })
+                .collect(),
+            Bend::Corners(corners) => std::iter::once(self.from)
+                .chain(corners.iter().copied())
+                .chain(std::iter::once(self.to))
+                .collect(),
+        }
+    }
+
+    /// Point halfway along the route.
+    pub(super) fn midpoint(&self) -> Point{
+        match &self.bend {
+            Bend::Straight => Point::new(
+                (self.from.x + self.to.x) / 2.0,
+                (self.from.y + self.to.y) / 2.0,
+            ),
+            Bend::Curve(control) => Point::new(
+                0.25 * self.from.x + 0.5 * control.x + 0.25 * self.to.x,
+                0.25 * self.from.y + 0.5 * control.y + 0.25 * self.to.y,
+            ),
+            Bend::Corners(_) => {
+                let points = self.polyline();
+                let length: f32 = points.windows(2).map(|s| s[0].distance(s[1])).sum();
+                let mut remaining = length / 2.0;
+                for segment in points.windows(2) {
+                    let (a, b) = (segment[0], segment[1]);
+                    let step = a.distance(b);
+                    if step > 0.0 && remaining <= step {
+                        return a + (b - a) * (remaining / step);
+                    }
+                    remaining -= step;
+                }
+                self.to
+            }
+        }
+    }
+
+    /// Point the route arrives from at its end, giving its final direction.
+    pub(super) fn approach(&self) -> Point{
+        match &self.bend {
+            Bend::Straight => self.from,
+            Bend::Curve(control) => *control,
+            Bend::Corners(corners) => corners.last().copied().unwrap_or(self.from),
+        }
+    }
+
+    pub(super) fn distance_to(&self, point: Point) -> f32{
+        self.polyline()
+            .windows(2)
+            .map(|segment| distance_to_segment(point, segment[0], segment[1]))
+            .fold(f32::INFINITY, f32::min)
+    }
+}
+
+impl Edge {
+    const ARROW_LENGTH: f32 = 18.0;
+    const ARROW_WIDTH: f32 = 12.0;
+    pub(super) const LABEL_SIZE: f32 = 14.0;
+    pub(super) const LABEL_PADDING: f32 = 4.0;
+
+    pub(super) fn new(id: u32, from: u32, to: u32) -> Self{
+        Edge{
+            id,
+            from,
+            to,
+            from_port: None,
+            to_port: None,
+            label: None,
+            is_selected: false,
+            weight: None,
+            width: 1.0,
+            line_dash: LineStyle::default(),
+            layer: String::from(Layer::DEFAULT),
+            lane: 0.0,
+            cache: GeometryCache::default(),
+        }
+    }
+
+    /// Key of what `draw` would draw with the same arguments, on top of
+    /// the view's key.
+    pub(super) fn draw_key(&self, view: &DrawKey, route: &EdgeRoute, label: Option<&str>, theme: &Theme) -> u64{
+        let mut key = view.clone()
+            .point(route.from)
+            .point(route.to)
+            .value(self.is_selected)
+            .float(self.width)
+            .value(self.line_dash)
+            .value(label.or(self.label.as_deref()))
+            .theme(theme);
+        match &route.bend {
+            Bend::Straight => {}
+            Bend::Curve(control) => key = key.value(1u8).point(*control),
+            Bend::Corners(corners) => {
+                key = key.value(2u8);
+                for corner in corners {
+                    key = key.point(*corner);
+                }
+            }
+        }
+        key.finish()
+    }
+
+    /// Draws the edge along `route`, with an arrowhead whose tip sits on the
+    /// end of the route. `label` overrides the edge's own label, e.g. while
+    /// it is being edited. Labels are left out unless `detailed`.
+    pub(super) fn draw(&self, frame: &mut Frame, route: &EdgeRoute, label: Option<&str>, scale: f32, theme: &Theme, detailed: bool) {
+        let color = self.color(theme);
+        let width = 5.0 * scale * self.width;
+        let segments = self.line_dash.segments(width);
+        let line_dash = match &segments {
+            Some(segments) => LineDash{ segments, offset: 0 },
+            None => LineDash::default(),
+        };
+        let line_cap = match self.line_dash {
+            LineStyle::Dotted => LineCap::Round,
+            _ => LineCap::default(),
+        };
+        frame.stroke(&route.path(), Stroke{
+            color,
+            width,
+            line_cap,
+            line_dash,
+            ..Stroke::default()
+        });
+
+        if let Some([tip, left, right]) = Self::arrowhead(route.approach(), route.to, scale) {
+            let arrowhead = Path::new(|builder| {
+                builder.move_to(tip);
+                builder.line_to(left);
+                builder.line_to(right);
+                builder.close();
+            });
+            frame.fill(&arrowhead, color);
+        }
+
+        let label = label.or(self.label.as_deref()).unwrap_or_default();
+        if detailed && !label.is_empty() {
+            let size = Self::LABEL_SIZE * scale;
+            let padding = Self::LABEL_PADDING * scale;
+            // Glyph metrics aren't available here, so estimate the width.
+            let text_size = Size::new(label.chars().count() as f32 * size * 0.6, size);
+            let center = route.midpoint();
+            let background = Path::rectangle(
+                Point::new(
+                    center.x - text_size.width / 2.0 - padding,
+                    center.y - text_size.height / 2.0 - padding,
+                ),
+                Size::new(text_size.width + 2.0 * padding, text_size.height + 2.0 * padding),
+            );
+            frame.fill(&background, theme.backdrop());
+            frame.fill_text(Text{
+                content: label.to_string(),
+                position: center,
+                color,
+                size,
+                horizontal_alignment: alignment::Horizontal::Center,
+                vertical_alignment: alignment::Vertical::Center,
+                ..Text::default()
+            });
+        }
+    }
+
+    pub(super) fn color(&self, theme: &Theme) -> Color {
+        if self.is_selected{
+            theme.selection
+        }else{
+            theme.line
+        }
+    }
+
+    /// Corners of the arrowhead for an edge arriving at `to` from the
+    /// direction of `from`, tip first.
+    pub(super) fn arrowhead(from: Point, to: Point, scale: f32) -> Option<[Point; 3]> {
+        let direction = to - from;
+        let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+        if length == 0.0 {
+            return None;
+        }
+        let unit = direction * (1.0 / length);
+        let normal = Vector::new(-unit.y, unit.x);
+        let base = to - unit * (Self::ARROW_LENGTH * scale);
+        let half_width = Self::ARROW_WIDTH * scale / 2.0;
+        Some([to, base + normal * half_width, base - normal * half_width])
+    }
+}
+
+impl Network{
+    /// Relative stroke widths of the lightest and heaviest weighted edges.
+    const EDGE_WIDTH_RANGE: (f32, f32) = (0.4, 3.0);
+
+    /// Checks that an edge from `from` to `to` may be added: both nodes
+    /// exist, they differ, and no edge goes the same way between them yet.
+    /// An edge in the opposite direction is fine.
+    pub fn check_edge(&self, from: u32, to: u32) -> Result<(), EdgeError>{
+        self.check_edge_except(from, to, None)
+    }
+
+    /// Like `check_edge`, but an edge with the id `except` doesn't count as
+    /// a duplicate, e.g. because it is the one being changed.
+    pub(super) fn check_edge_except(&self, from: u32, to: u32, except: Option<u32>) -> Result<(), EdgeError>{
+        for id in [from, to] {
+            if self.node(id).is_none() {
+                return Err(EdgeError::UnknownNode(id));
+            }
+        }
+        if from == to {
+            return Err(EdgeError::SelfLoop(from));
+        }
+        match self.edges.iter().find(|edge| edge.from == from && edge.to == to && Some(edge.id) != except) {
+            Some(edge) => Err(EdgeError::Duplicate(edge.id)),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets or clears the weight of an edge. Weighted edges are drawn
+    /// thicker the heavier they are compared to the other weighted edges.
+    /// NaN and infinite weights are ignored.
+    pub fn set_edge_weight(&mut self, edge_id: u32, weight: Option<f32>){
+        if weight.is_some_and(|weight| !weight.is_finite()) {
+            return;
+        }
+        if let Some(edge) = self.edges.iter_mut().find(|x| x.id == edge_id) {
+            edge.weight = weight;
+            self.mark_dirty();
+        }
+    }
+
+    /// Sets the dash pattern of an edge.
+    pub fn set_edge_line_style(&mut self, edge_id: u32, style: LineStyle){
+        if let Some(edge) = self.edges.iter_mut().find(|x| x.id == edge_id) {
+            edge.line_dash = style;
+            self.mark_dirty();
+        }
+    }
+
+    /// Gives the selected edges the line style after the one of the first
+    /// selected edge.
+    pub fn cycle_selected_line_styles(&mut self){
+        let style = match self.edges.iter().find(|edge| edge.is_selected) {
+            Some(edge) => edge.line_dash.next(),
+            None => return,
+        };
+        for edge in self.edges.iter_mut().filter(|edge| edge.is_selected) {
+            edge.line_dash = style;
+        }
+        self.mark_dirty();
+    }
+
+    /// Recomputes every edge's stroke width from its weight, spreading the
+    /// weights present over `EDGE_WIDTH_RANGE`. Edges without a weight, or
+    /// with the same weight as all the others, get the default width.
+    pub(super) fn fit_edge_widths(&mut self){
+        let weights = self.edges.iter().filter_map(|edge| edge.weight);
+        let lightest = weights.clone().fold(f32::INFINITY, f32::min);
+        let heaviest = weights.fold(f32::NEG_INFINITY, f32::max);
+        let (thinnest, thickest) = Self::EDGE_WIDTH_RANGE;
+        for edge in self.edges.iter_mut() {
+            edge.width = match edge.weight {
+                Some(weight) if heaviest > lightest => {
+                    thinnest + (weight - lightest) / (heaviest - lightest) * (thickest - thinnest)
+                }
+                _ => 1.0,
+            };
+        }
+    }
+
+    /// Spreads edges that connect the same two nodes, in either direction,
+    /// over lanes so they are drawn, and can be clicked, apart.
+    pub(super) fn fit_parallel_edges(&mut self){
+        let pair = |edge: &Edge| (edge.from.min(edge.to), edge.from.max(edge.to));
+        // Edges between each pair, and how many got their lane so far.
+        let mut counts: HashMap<(u32, u32), (usize, usize)> = HashMap::new();
+        for edge in &self.edges {
+            counts.entry(pair(edge)).or_default().0 += 1;
+        }
+        for edge in self.edges.iter_mut() {
+            let (total, assigned) = counts.get_mut(&pair(edge)).expect("every pair was counted");
+            let lane = *assigned as f32 - (*total - 1) as f32 / 2.0;
+            *assigned += 1;
+            edge.lane = if edge.from <= edge.to { lane } else { -lane };
+        }
+    }
+}
+
+fn distance_to_segment(point: Point, a: Point, b: Point) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.x * ab.x + ab.y * ab.y;
+    if length_squared == 0.0 {
+        return point.distance(a);
+    }
+    let ap = point - a;
+    let t = ((ap.x * ab.x + ap.y * ab.y) / length_squared).clamp(0.0, 1.0);
+    point.distance(a + ab * t)
+}
+
+#[cfg(test)]
+mod tests{
+    use iced::Rectangle;
+
+    use crate::network::{EdgeError, Network, Node};
+
+    fn two_nodes() -> (Network, u32, u32){
+        let mut network = Network::empty();
+        let a = network.add_node(Node::with_bounds(Rectangle{ x: 0.0, y: 0.0, width: 100.0, height: 100.0 }));
+        let b = network.add_node(Node::with_bounds(Rectangle{ x: 300.0, y: 0.0, width: 100.0, height: 100.0 }));
+        (network, a, b)
+    }
+
+    #[test]
+    fn self_loops_are_refused(){
+        let (mut network, a, _) = two_nodes();
+        assert_eq!(network.check_edge(a, a), Err(EdgeError::SelfLoop(a)));
+        assert_eq!(network.add_edge(a, a), Err(EdgeError::SelfLoop(a)));
+        assert_eq!(network.degree(a), 0);
+    }
+
+    #[test]
+    fn duplicates_are_refused_but_the_opposite_direction_is_accepted(){
+        let (mut network, a, b) = two_nodes();
+        let first = network.add_edge(a, b).expect("the nodes aren't connected yet");
+        assert_eq!(network.check_edge(a, b), Err(EdgeError::Duplicate(first)));
+        assert_eq!(network.add_edge(a, b), Err(EdgeError::Duplicate(first)));
+        let back = network.add_edge(b, a).expect("the opposite direction is allowed");
+        assert_ne!(back, first);
+        assert_eq!(network.degree(a), 2);
+    }
+
+    #[test]
+    fn weights_that_are_not_finite_are_ignored(){
+        let (mut network, a, b) = two_nodes();
+        let edge = network.add_edge(a, b).expect("the nodes aren't connected yet");
+        network.set_edge_weight(edge, Some(2.0));
+        network.set_edge_weight(edge, Some(f32::NAN));
+        network.set_edge_weight(edge, Some(f32::INFINITY));
+        assert_eq!(network.edges[0].weight, Some(2.0));
+        network.set_edge_weight(edge, None);
+        assert_eq!(network.edges[0].weight, None);
+    }
+}
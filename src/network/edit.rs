@@ -0,0 +1,100 @@
+use iced::keyboard::KeyCode;
+use iced_graphics::canvas::event;
+
+use super::{Network, NetworkMessage};
+
+/// What an inline label edit writes to when committed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum EditTarget{
+    Node(u32),
+    Edge(u32),
+    /// A text annotation.
+    Text(u32),
+}
+
+/// Inline label edit in progress. The text is only applied on commit.
+pub(super) struct LabelEdit{
+    pub(super) target: EditTarget,
+    pub(super) text: String,
+}
+
+impl Network{
+    /// Starts editing the label of the selected node or, if no node is
+    /// selected, of the selected edge. Needs exactly one selected item.
+    pub(super) fn start_label_edit(&mut self){
+        let nodes: Vec<_> = self.nodes.iter().filter(|n| n.is_selected).collect();
+        let edges: Vec<_> = self.edges.iter().filter(|e| e.is_selected).collect();
+        let edit = match (nodes.as_slice(), edges.as_slice()) {
+            ([node], _) => LabelEdit{
+                target: EditTarget::Node(node.id),
+                text: node.label.clone(),
+            },
+            ([], [edge]) => LabelEdit{
+                target: EditTarget::Edge(edge.id),
+                text: edge.label.clone().unwrap_or_default(),
+            },
+            _ => return,
+        };
+        self.label_edit = Some(edit);
+        self.request_redraw();
+    }
+
+    pub(super) fn handle_edit_key(&mut self, key_code: KeyCode) -> (event::Status, Option<NetworkMessage>){
+        match key_code {
+            KeyCode::Escape => {
+                self.label_edit = None;
+                self.drop_empty_texts();
+                self.request_redraw();
+            }
+            KeyCode::Enter | KeyCode::NumpadEnter => self.commit_label_edit(),
+            KeyCode::Backspace => {
+                if let Some(edit) = self.label_edit.as_mut() {
+                    edit.text.pop();
+                    self.request_redraw();
+                }
+            }
+            _ => {}
+        }
+        (event::Status::Captured, None)
+    }
+
+    pub(super) fn handle_edit_character(&mut self, c: char){
+        if c.is_control() {
+            return;
+        }
+        if let Some(edit) = self.label_edit.as_mut() {
+            edit.text.push(c);
+            self.request_redraw();
+        }
+    }
+
+    pub(super) fn commit_label_edit(&mut self){
+        if let Some(edit) = self.label_edit.take() {
+            match edit.target {
+                EditTarget::Node(id) => {
+                    if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+                        node.label = edit.text;
+                    }
+                }
+                EditTarget::Edge(id) => {
+                    let label = Some(edit.text).filter(|text| !text.is_empty());
+                    self.set_edge_label(id, label);
+                }
+                EditTarget::Text(id) => {
+                    if let Some(text) = self.texts.iter_mut().find(|x| x.id == id) {
+                        text.content = edit.text;
+                    }
+                    self.drop_empty_texts();
+                }
+            }
+            self.mark_dirty();
+        }
+    }
+
+    /// Text being typed for the given target, if it is being edited.
+    pub(super) fn edit_text(&self, target: EditTarget) -> Option<&str>{
+        self.label_edit.as_ref()
+            .filter(|edit| edit.target == target)
+            .map(|edit| edit.text.as_str())
+    }
+}
@@ -0,0 +1,143 @@
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use iced::{Color, Point, Rectangle, Size, Vector};
+use iced_graphics::canvas::{Cache, Frame, Geometry};
+
+use super::Theme;
+
+/// Tessellated drawing of a single node or edge. It is kept until the key
+/// it was drawn with changes, so moving one node leaves the geometry of
+/// the others alone.
+#[derive(Default)]
+pub(super) struct GeometryCache{
+    cache: RefCell<Cache>,
+    key: Cell<Option<u64>>,
+}
+
+impl GeometryCache{
+    /// Returns the cached geometry if it was drawn with `key`, or draws it
+    /// again with `draw_fn`.
+    pub(super) fn draw(&self, key: u64, size: Size, draw_fn: impl Fn(&mut Frame)) -> Geometry{
+        let mut cache = self.cache.borrow_mut();
+        if self.key.replace(Some(key)) != Some(key) {
+            cache.clear();
+        }
+        cache.draw(size, draw_fn)
+    }
+}
+
+/// Geometry of the items drawn in one frame, in draw order. While the view
+/// holds still every item comes from its own cache, so a change to one item
+/// only redraws that item. Items are cached in screen space though, so a
+/// pan or zoom invalidates them all, and drawing them into a frame each
+/// costs far more than drawing them into one shared frame. So while the
+/// view moves everything goes into shared frames, and once it stops the
+/// caches are filled a few hundred items per frame; the items still waiting
+/// are drawn into shared frames in the meantime.
+pub(super) struct Scene{
+    size: Size,
+    settled: bool,
+    /// Stale caches that may still be redrawn in this frame.
+    budget: usize,
+    /// Shared frame collecting the items drawn since the last cached one.
+    run: Option<Frame>,
+    geometry: Vec<Geometry>,
+}
+
+impl Scene{
+    /// Caches filled per frame once the view settles.
+    const FILL_BUDGET: usize = 500;
+
+    /// `settled` tells whether the view is the same as in the last frame.
+    pub(super) fn new(size: Size, settled: bool) -> Self{
+        Scene{ size, settled, budget: Self::FILL_BUDGET, run: None, geometry: Vec::new() }
+    }
+
+    /// Adds an item drawn by `draw_fn`, reusing its cached geometry if it
+    /// was drawn with the same `key` before.
+    pub(super) fn item(&mut self, cache: &GeometryCache, key: u64, draw_fn: impl Fn(&mut Frame)){
+        let current = cache.key.get() == Some(key);
+        if self.settled && (current || self.budget > 0) {
+            if !current {
+                self.budget -= 1;
+            }
+            self.end_run();
+            self.geometry.push(cache.draw(key, self.size, draw_fn));
+        } else {
+            self.uncached(draw_fn);
+        }
+    }
+
+    /// Adds something that isn't worth caching.
+    pub(super) fn uncached(&mut self, draw_fn: impl FnOnce(&mut Frame)){
+        let size = self.size;
+        draw_fn(self.run.get_or_insert_with(|| Frame::new(size)));
+    }
+
+    fn end_run(&mut self){
+        if let Some(run) = self.run.take() {
+            self.geometry.push(run.into_geometry());
+        }
+    }
+
+    pub(super) fn into_geometry(mut self) -> Vec<Geometry>{
+        self.end_run();
+        self.geometry
+    }
+}
+
+/// Hash of everything a drawing depends on, to tell whether a cached
+/// geometry is still current. Floats are hashed by their bits.
+#[derive(Clone)]
+pub(super) struct DrawKey(DefaultHasher);
+
+impl DrawKey{
+    pub(super) fn new() -> Self{
+        DrawKey(DefaultHasher::new())
+    }
+
+    pub(super) fn value(mut self, value: impl Hash) -> Self{
+        value.hash(&mut self.0);
+        self
+    }
+
+    pub(super) fn float(self, value: f32) -> Self{
+        self.value(value.to_bits())
+    }
+
+    pub(super) fn point(self, point: Point) -> Self{
+        self.float(point.x).float(point.y)
+    }
+
+    pub(super) fn vector(self, vector: Vector) -> Self{
+        self.float(vector.x).float(vector.y)
+    }
+
+    pub(super) fn size(self, size: Size) -> Self{
+        self.float(size.width).float(size.height)
+    }
+
+    pub(super) fn rectangle(self, rectangle: Rectangle) -> Self{
+        self.point(rectangle.position()).size(rectangle.size())
+    }
+
+    pub(super) fn color(self, color: Color) -> Self{
+        self.float(color.r).float(color.g).float(color.b).float(color.a)
+    }
+
+    pub(super) fn theme(self, theme: &Theme) -> Self{
+        self.color(theme.background)
+            .color(theme.node)
+            .color(theme.label)
+            .color(theme.line)
+            .color(theme.grid)
+            .color(theme.selection)
+            .color(theme.annotation)
+    }
+
+    pub(super) fn finish(&self) -> u64{
+        self.0.finish()
+    }
+}
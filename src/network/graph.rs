@@ -0,0 +1,393 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::Entry;
+
+use iced::{Color, Size};
+
+use super::{Network, SerNetwork};
+
+/// Hop distances from the node that was selected when the highlight was
+/// made. Dropped as soon as the selection changes.
+pub(super) struct Highlight{
+    distances: HashMap<u32, u32>,
+    farthest: u32,
+    selection: Vec<u32>,
+}
+
+impl Network{
+    /// Color of the start node of a breadth-first highlight.
+    const HIGHLIGHT_NEAR: Color = Color::from_rgb(1.0, 0.85, 0.2);
+    /// Color of the farthest reachable nodes.
+    const HIGHLIGHT_FAR: Color = Color::from_rgb(0.2, 0.3, 0.8);
+    /// Smallest and largest node sides used by `toggle_size_by_degree`.
+    const DEGREE_SIZE_RANGE: (f32, f32) = (60.0, 180.0);
+    pub(super) const COMPONENT_PALETTE: [Color; 8] = [
+        Color::from_rgb(0.89, 0.29, 0.20),
+        Color::from_rgb(0.20, 0.55, 0.85),
+        Color::from_rgb(0.30, 0.69, 0.29),
+        Color::from_rgb(0.60, 0.31, 0.64),
+        Color::from_rgb(1.00, 0.50, 0.00),
+        Color::from_rgb(0.65, 0.34, 0.16),
+        Color::from_rgb(0.97, 0.51, 0.75),
+        Color::from_rgb(0.40, 0.76, 0.65),
+    ];
+
+    /// Outgoing neighbors of every node, following edge direction.
+    pub fn adjacency(&self) -> HashMap<u32, Vec<u32>>{
+        let mut adjacency: HashMap<u32, Vec<u32>> = self.nodes.iter()
+            .map(|node| (node.id, Vec::new()))
+            .collect();
+        for edge in &self.edges {
+            if let Some(neighbors) = adjacency.get_mut(&edge.from) {
+                neighbors.push(edge.to);
+            }
+        }
+        adjacency
+    }
+
+    /// Nodes connected to `id` by an edge in either direction, each listed
+    /// once, in edge order.
+    pub fn neighbors(&self, id: u32) -> Vec<u32>{
+        let mut neighbors = Vec::new();
+        for edge in &self.edges {
+            let other = if edge.from == id {
+                edge.to
+            } else if edge.to == id {
+                edge.from
+            } else {
+                continue;
+            };
+            if !neighbors.contains(&other) {
+                neighbors.push(other);
+            }
+        }
+        neighbors
+    }
+
+    /// Number of edge ends at `id`. Parallel edges each count, and a
+    /// self-loop counts twice.
+    pub fn degree(&self, id: u32) -> usize{
+        self.edges.iter()
+            .map(|edge| (edge.from == id) as usize + (edge.to == id) as usize)
+            .sum()
+    }
+
+    /// Targets of the edges leaving `id`, each listed once.
+    pub fn successors(&self, id: u32) -> Vec<u32>{
+        let mut successors = Vec::new();
+        for edge in self.edges.iter().filter(|edge| edge.from == id) {
+            if !successors.contains(&edge.to) {
+                successors.push(edge.to);
+            }
+        }
+        successors
+    }
+
+    /// Sources of the edges entering `id`, each listed once.
+    pub fn predecessors(&self, id: u32) -> Vec<u32>{
+        let mut predecessors = Vec::new();
+        for edge in self.edges.iter().filter(|edge| edge.to == id) {
+            if !predecessors.contains(&edge.from) {
+                predecessors.push(edge.from);
+            }
+        }
+        predecessors
+    }
+
+    /// Number of edges on the shortest path from `start` to every node
+    /// reachable from it, following edge direction. `start` itself is at 0.
+    pub fn bfs_from(&self, start: u32) -> HashMap<u32, u32>{
+        let mut distances = HashMap::new();
+        if self.node(start).is_none() {
+            return distances;
+        }
+        let adjacency = self.adjacency();
+        let mut queue = VecDeque::from([start]);
+        distances.insert(start, 0);
+        while let Some(id) = queue.pop_front() {
+            let distance = distances[&id];
+            for &neighbor in adjacency.get(&id).into_iter().flatten() {
+                if let Entry::Vacant(entry) = distances.entry(neighbor) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Whether following edge direction can lead from some node back to
+    /// itself. Self-loops count.
+    pub fn has_cycle(&self) -> bool{
+        self.find_cycle().is_some()
+    }
+
+    /// Nodes of a directed cycle in path order, the edge back to the first
+    /// node being implied, or `None` if the graph is acyclic. A self-loop is
+    /// a cycle of one node.
+    pub fn find_cycle(&self) -> Option<Vec<u32>>{
+        let adjacency = self.adjacency();
+        // Nodes on the current path are `false`, finished ones `true`.
+        let mut finished: HashMap<u32, bool> = HashMap::new();
+        for node in &self.nodes {
+            if finished.contains_key(&node.id) {
+                continue;
+            }
+            let mut path = vec![node.id];
+            let mut stack = vec![(node.id, 0)];
+            finished.insert(node.id, false);
+            while let Some((id, next)) = stack.last_mut() {
+                match adjacency.get(id).and_then(|neighbors| neighbors.get(*next)) {
+                    Some(&neighbor) => {
+                        *next += 1;
+                        match finished.get(&neighbor) {
+                            Some(false) => {
+                                let start = path.iter().position(|id| *id == neighbor)?;
+                                return Some(path.split_off(start));
+                            }
+                            Some(true) => {}
+                            None => {
+                                finished.insert(neighbor, false);
+                                path.push(neighbor);
+                                stack.push((neighbor, 0));
+                            }
+                        }
+                    }
+                    None => {
+                        finished.insert(*id, true);
+                        path.pop();
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Index of the connected component of every node, ignoring edge
+    /// direction. Components are numbered in the order of their first node.
+    pub fn connected_components(&self) -> HashMap<u32, usize>{
+        let index: HashMap<u32, usize> = self.nodes.iter()
+            .enumerate()
+            .map(|(i, node)| (node.id, i))
+            .collect();
+        let mut parents: Vec<usize> = (0..self.nodes.len()).collect();
+        fn root(parents: &mut [usize], mut i: usize) -> usize{
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+        for edge in &self.edges {
+            if let (Some(&a), Some(&b)) = (index.get(&edge.from), index.get(&edge.to)) {
+                let (a, b) = (root(&mut parents, a), root(&mut parents, b));
+                parents[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut components = HashMap::new();
+        let mut numbers = HashMap::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            let next = numbers.len();
+            let number = *numbers.entry(root(&mut parents, i)).or_insert(next);
+            components.insert(node.id, number);
+        }
+        components
+    }
+
+    /// The nodes in `ids` and the edges between them, e.g. to export part of
+    /// a graph. Edges with only one end in `ids` are left out, and groups
+    /// keep only their members in `ids`. Ids are kept.
+    pub fn subgraph(&self, ids: &HashSet<u32>) -> Network{
+        let mut graph = SerNetwork::from(self);
+        graph.nodes.retain(|node| ids.contains(&node.id));
+        graph.edges.retain(|edge| ids.contains(&edge.from) && ids.contains(&edge.to));
+        for group in graph.groups.iter_mut() {
+            group.member_ids.retain(|id| ids.contains(id));
+        }
+        graph.groups.retain(|group| !group.member_ids.is_empty());
+        graph.annotations.clear();
+        graph.texts.clear();
+        Network::try_from(graph).expect("a subgraph refers only to its own nodes")
+    }
+
+    /// Fills every connected component with its own color from a palette,
+    /// or draws the node colors again if components are already colored.
+    /// Only the view changes: the node colors, and what is saved, stay as
+    /// they are.
+    pub fn toggle_component_colors(&mut self){
+        self.component_colors = match self.component_colors {
+            Some(_) => None,
+            None => Some(self.component_palette()),
+        };
+        self.request_redraw();
+    }
+
+    /// A color from the palette for every node, the same for the nodes of
+    /// a connected component.
+    pub(super) fn component_palette(&self) -> HashMap<u32, Color>{
+        self.connected_components().into_iter()
+            .map(|(id, component)| (id, Self::COMPONENT_PALETTE[component % Self::COMPONENT_PALETTE.len()]))
+            .collect()
+    }
+
+    /// Resizes every node so its area grows with its degree: isolated nodes
+    /// get sides of `min`, the best connected ones sides of `max`. Nodes
+    /// keep their centers. The sizes from before are kept so
+    /// `restore_sizes` can put them back.
+    pub fn size_by_degree(&mut self, min: f32, max: f32){
+        let degrees: Vec<usize> = self.nodes.iter().map(|node| self.degree(node.id)).collect();
+        let highest = degrees.iter().copied().max().unwrap_or(0);
+        if self.original_sizes.is_none() {
+            self.original_sizes = Some(self.nodes.iter()
+                .map(|node| (node.id, node.bounds.size()))
+                .collect());
+        }
+        for (node, degree) in self.nodes.iter_mut().zip(degrees) {
+            let share = if highest == 0 { 0.0 } else { degree as f32 / highest as f32 };
+            let side = min + (max - min) * share.sqrt();
+            node.set_size(Size::new(side, side));
+        }
+        self.mark_dirty();
+    }
+
+    /// Puts back the node sizes from before `size_by_degree`.
+    pub fn restore_sizes(&mut self){
+        if let Some(original) = self.original_sizes.take() {
+            for node in self.nodes.iter_mut() {
+                if let Some(size) = original.get(&node.id) {
+                    node.set_size(*size);
+                }
+            }
+            self.mark_dirty();
+        }
+    }
+
+    /// Switches between sizing nodes by degree and their own sizes.
+    pub fn toggle_size_by_degree(&mut self){
+        if self.original_sizes.is_some() {
+            self.restore_sizes();
+        } else {
+            self.size_by_degree(Self::DEGREE_SIZE_RANGE.0, Self::DEGREE_SIZE_RANGE.1);
+        }
+    }
+
+    /// Ids of the selected nodes, in drawing order.
+    pub fn selected_node_ids(&self) -> Vec<u32>{
+        self.nodes.iter()
+            .filter(|node| node.is_selected)
+            .map(|node| node.id)
+            .collect()
+    }
+
+    /// Colors the nodes reachable from the single selected node by hop
+    /// count, until the selection changes. Does nothing unless exactly one
+    /// node is selected.
+    pub fn highlight_reachable(&mut self){
+        let selection = self.selected_node_ids();
+        if let [start] = selection[..] {
+            let distances = self.bfs_from(start);
+            let farthest = distances.values().copied().max().unwrap_or(0);
+            self.highlight = Some(Highlight{ distances, farthest, selection });
+            self.request_redraw();
+        }
+    }
+
+    /// Drops the highlight if the selection it was made for has changed.
+    pub(super) fn expire_highlight(&mut self){
+        let expired = self.highlight.as_ref()
+            .is_some_and(|highlight| highlight.selection != self.selected_node_ids());
+        if expired {
+            self.highlight = None;
+            self.request_redraw();
+        }
+    }
+
+    /// Highlight color of a node, if it is part of the current highlight.
+    pub(super) fn highlight_color(&self, node_id: u32) -> Option<Color>{
+        let highlight = self.highlight.as_ref()?;
+        let distance = *highlight.distances.get(&node_id)?;
+        let t = if highlight.farthest == 0 {
+            0.0
+        } else {
+            distance as f32 / highlight.farthest as f32
+        };
+        let (near, far) = (Self::HIGHLIGHT_NEAR, Self::HIGHLIGHT_FAR);
+        Some(Color::from_rgb(
+            near.r + (far.r - near.r) * t,
+            near.g + (far.g - near.g) * t,
+            near.b + (far.b - near.b) * t,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::network::Network;
+
+    /// Network with nodes 1 to `count` and the given edges, loaded as-is.
+    fn graph(count: u32, edges: &[(u32, u32)]) -> Network{
+        let nodes: Vec<String> = (1..=count)
+            .map(|id| format!(r#"{{"id": {}, "x": {}, "y": 0}}"#, id, id * 200))
+            .collect();
+        let edges: Vec<String> = edges.iter()
+            .map(|(from, to)| format!(r#"{{"from": {}, "to": {}}}"#, from, to))
+            .collect();
+        let document = format!(r#"{{"nodes": [{}], "edges": [{}]}}"#, nodes.join(","), edges.join(","));
+        Network::import_json(&document).expect("the test graph is valid")
+    }
+
+    #[test]
+    fn acyclic_graph_has_no_cycle(){
+        let network = graph(4, &[(1, 2), (1, 3), (2, 4), (3, 4)]);
+        assert!(!network.has_cycle());
+        assert_eq!(network.find_cycle(), None);
+    }
+
+    #[test]
+    fn three_node_cycle_is_found_in_path_order(){
+        let network = graph(4, &[(4, 1), (1, 2), (2, 3), (3, 1)]);
+        assert!(network.has_cycle());
+        assert_eq!(network.find_cycle(), Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn self_loop_is_a_cycle_of_one_node(){
+        let network = graph(2, &[(1, 2), (2, 2)]);
+        assert!(network.has_cycle());
+        assert_eq!(network.find_cycle(), Some(vec![2]));
+    }
+
+    #[test]
+    fn isolated_node_has_no_neighbors(){
+        let network = graph(3, &[(1, 2)]);
+        assert_eq!(network.degree(3), 0);
+        assert!(network.neighbors(3).is_empty());
+        assert!(network.successors(3).is_empty());
+        assert!(network.predecessors(3).is_empty());
+    }
+
+    #[test]
+    fn parallel_edges_count_in_the_degree_but_neighbors_are_listed_once(){
+        let network = graph(3, &[(1, 2), (1, 2), (2, 1), (3, 1)]);
+        assert_eq!(network.degree(1), 4);
+        assert_eq!(network.degree(2), 3);
+        assert_eq!(network.neighbors(1), [2, 3]);
+        assert_eq!(network.successors(1), [2]);
+        assert_eq!(network.predecessors(1), [2, 3]);
+    }
+
+    #[test]
+    fn component_colors_are_drawn_but_not_saved(){
+        let mut network = graph(3, &[(1, 2)]);
+        let before = network.export_json();
+        network.toggle_component_colors();
+        assert!(!network.is_dirty());
+        assert_eq!(network.export_json(), before);
+        let fill = |network: &Network, id: u32| network.node_fill(network.node(id).unwrap());
+        assert_eq!(fill(&network, 1), fill(&network, 2));
+        assert_ne!(fill(&network, 1), fill(&network, 3));
+        network.toggle_component_colors();
+        assert_eq!(fill(&network, 1), network.theme().node);
+    }
+}
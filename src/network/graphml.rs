@@ -0,0 +1,68 @@
+use std::fmt::Write;
+
+use super::{LineStyle, Network};
+
+impl Network{
+    /// Serializes the graph as GraphML. Nodes carry their label and the
+    /// world position of their top-left corner as data keys, weighted edges
+    /// their weight and dashed or dotted edges their line style.
+    pub fn export_graphml(&self) -> String{
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" \
+            xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+            xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns \
+            http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n");
+        out.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+        out.push_str("  <key id=\"x\" for=\"node\" attr.name=\"x\" attr.type=\"double\"/>\n");
+        out.push_str("  <key id=\"y\" for=\"node\" attr.name=\"y\" attr.type=\"double\"/>\n");
+        out.push_str("  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n");
+        out.push_str("  <key id=\"line_style\" for=\"edge\" attr.name=\"line_style\" attr.type=\"string\"/>\n");
+        out.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+        for node in &self.nodes{
+            let _ = writeln!(out, "    <node id=\"n{}\">", node.id);
+            let _ = writeln!(out, "      <data key=\"label\">{}</data>", escape(&node.label));
+            let _ = writeln!(out, "      <data key=\"x\">{}</data>", node.bounds.x);
+            let _ = writeln!(out, "      <data key=\"y\">{}</data>", node.bounds.y);
+            out.push_str("    </node>\n");
+        }
+        for edge in &self.edges{
+            let mut data = String::new();
+            if let Some(weight) = edge.weight {
+                let _ = writeln!(data, "      <data key=\"weight\">{}</data>", weight);
+            }
+            if edge.line_dash != LineStyle::Solid {
+                let _ = writeln!(data, "      <data key=\"line_style\">{}</data>", edge.line_dash.name());
+            }
+            if data.is_empty() {
+                let _ = writeln!(out, "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"/>",
+                                 edge.id, edge.from, edge.to);
+            } else {
+                let _ = writeln!(out, "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">",
+                                 edge.id, edge.from, edge.to);
+                out.push_str(&data);
+                out.push_str("    </edge>\n");
+            }
+        }
+        out.push_str("  </graph>\n");
+        out.push_str("</graphml>\n");
+        out
+    }
+}
+
+/// Escapes the characters that are not allowed verbatim in XML text and
+/// attribute values.
+fn escape(text: &str) -> String{
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
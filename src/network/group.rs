@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use iced::{alignment, Color, Point, Rectangle, Size, Vector};
+use iced_graphics::canvas::{Frame, LineDash, Path, Stroke, Text};
+
+use super::{Network, Node, Theme};
+
+/// Container around a set of nodes. Expanded, it is drawn as a labelled box
+/// around its members; collapsed, the members are hidden behind a single box
+/// that their edges attach to.
+pub(super) struct Group{
+    pub(super) id: u32,
+    pub(super) member_ids: Vec<u32>,
+    pub(super) collapsed: bool,
+    /// World bounds of the box, kept in sync with the members by
+    /// `Network::fit_groups`.
+    pub(super) bounds: Rectangle,
+    pub(super) label: String,
+    pub(super) is_selected: bool,
+}
+
+impl Group{
+    /// Space between the members and the border of an expanded group.
+    const PADDING: f32 = 20.0;
+    const COLLAPSED_SIZE: Size = Size::new(140.0, 80.0);
+    const LABEL_SIZE: f32 = 14.0;
+
+    pub(super) fn stroke_color(&self, theme: &Theme) -> Color{
+        if self.is_selected {
+            theme.selection
+        } else {
+            Color{ a: 0.6, ..theme.line }
+        }
+    }
+
+    /// Draws the box, and its label if `detailed`.
+    pub(super) fn draw(&self, frame: &mut Frame, scale: f32, theme: &Theme, detailed: bool){
+        let body = Path::rectangle(self.bounds.position(), self.bounds.size());
+        let segments = [8.0 * scale, 6.0 * scale];
+        let line_dash = if self.collapsed {
+            LineDash::default()
+        } else {
+            LineDash{ segments: &segments, offset: 0 }
+        };
+        let fill = if self.collapsed { theme.node } else { Color{ a: 0.08, ..theme.line } };
+        frame.fill(&body, fill);
+        frame.stroke(&body, Stroke{
+            color: self.stroke_color(theme),
+            width: 2.0 * scale,
+            line_dash,
+            ..Stroke::default()
+        });
+        if !detailed {
+            return;
+        }
+
+        let (content, position, horizontal_alignment, vertical_alignment) = if self.collapsed {
+            (
+                format!("{} ({})", self.label, self.member_ids.len()),
+                self.bounds.center(),
+                alignment::Horizontal::Center,
+                alignment::Vertical::Center,
+            )
+        } else {
+            (
+                self.label.clone(),
+                self.bounds.position() + Vector::new(Self::PADDING / 2.0, Self::PADDING / 4.0),
+                alignment::Horizontal::Left,
+                alignment::Vertical::Top,
+            )
+        };
+        frame.fill_text(Text{
+            content,
+            position,
+            color: theme.label,
+            size: Self::LABEL_SIZE * scale,
+            horizontal_alignment,
+            vertical_alignment,
+            ..Text::default()
+        });
+    }
+
+    /// Corners of the box, for the raster export.
+    pub(super) fn outline(&self) -> Vec<Point>{
+        let b = self.bounds;
+        vec![
+            Point::new(b.x, b.y),
+            Point::new(b.x + b.width, b.y),
+            Point::new(b.x + b.width, b.y + b.height),
+            Point::new(b.x, b.y + b.height),
+        ]
+    }
+}
+
+impl Network{
+    /// Puts the selected nodes in a new expanded group and selects it. Nodes
+    /// already in another group are moved out of it. Returns the id of the
+    /// new group, or `None` if no node is selected.
+    pub fn group_selected(&mut self) -> Option<u32>{
+        let member_ids: Vec<u32> = self.nodes.iter()
+            .filter(|node| node.is_selected && !self.is_hidden(node))
+            .map(|node| node.id)
+            .collect();
+        if member_ids.is_empty() {
+            return None;
+        }
+        for group in self.groups.iter_mut() {
+            group.member_ids.retain(|id| !member_ids.contains(id));
+        }
+        self.groups.retain(|group| !group.member_ids.is_empty());
+
+        self.unselect_all_nodes();
+        self.unselect_all_edges();
+        self.unselect_all_groups();
+        let id = self.allocate_id();
+        self.groups.push(Group{
+            id,
+            member_ids,
+            collapsed: false,
+            bounds: Rectangle::default(),
+            label: format!("Group {}", id),
+            is_selected: true,
+        });
+        self.mark_dirty();
+        Some(id)
+    }
+
+    /// Dissolves the selected groups, leaving their members where they are.
+    pub fn ungroup_selected(&mut self){
+        let count = self.groups.len();
+        self.groups.retain(|group| !group.is_selected);
+        if self.groups.len() != count {
+            self.mark_dirty();
+        }
+    }
+
+    /// Collapses the selected groups that are expanded and expands the ones
+    /// that are collapsed.
+    pub fn toggle_selected_groups(&mut self){
+        let mut changed = false;
+        for group in self.groups.iter_mut().filter(|group| group.is_selected) {
+            group.collapsed = !group.collapsed;
+            changed = true;
+        }
+        if changed {
+            self.mark_dirty();
+        }
+    }
+
+    pub fn set_group_collapsed(&mut self, group_id: u32, collapsed: bool){
+        if let Some(group) = self.groups.iter_mut().find(|x| x.id == group_id) {
+            if group.collapsed != collapsed {
+                group.collapsed = collapsed;
+                self.mark_dirty();
+            }
+        }
+    }
+
+    /// The collapsed group hiding the given node, if any.
+    pub(super) fn collapsed_group_of(&self, node_id: u32) -> Option<&Group>{
+        self.groups.iter()
+            .find(|group| group.collapsed && group.member_ids.contains(&node_id))
+    }
+
+    /// Whether the node is out of sight: in a collapsed group or on a
+    /// hidden layer.
+    pub(super) fn is_hidden(&self, node: &Node) -> bool{
+        !self.is_layer_visible(&node.layer) || self.collapsed_group_of(node.id).is_some()
+    }
+
+    /// Returns the id of the group whose box contains the given world
+    /// position, topmost first.
+    pub fn group_at(&self, world: Point) -> Option<u32>{
+        self.groups.iter()
+            .rev()
+            .find(|group| group.bounds.contains(world))
+            .map(|group| group.id)
+    }
+
+    pub(super) fn unselect_all_groups(&mut self){
+        for group in self.groups.iter_mut(){
+            if group.is_selected {
+                group.is_selected = false;
+                self.groups_cache.clear();
+            }
+        }
+    }
+
+    /// Members that move when their group is dragged, in member order.
+    /// Locked and pinned members stay put, and so do members on hidden or
+    /// locked layers.
+    fn movable_members(&self, group_id: u32) -> Vec<&Node>{
+        let group = match self.groups.iter().find(|x| x.id == group_id) {
+            Some(group) => group,
+            None => return Vec::new(),
+        };
+        group.member_ids.iter()
+            .filter_map(|id| self.node(*id))
+            .filter(|node| !node.is_fixed() && self.is_layer_available(&node.layer))
+            .collect()
+    }
+
+    /// Member a group is dragged by: the first one that can move.
+    pub(super) fn group_anchor(&self, group_id: u32) -> Option<&Node>{
+        self.movable_members(group_id).first().copied()
+    }
+
+    /// Moves the members of a group that can move, so that the group's
+    /// anchor ends up at `new_pos`; see `group_anchor`. The box is refitted
+    /// around all members.
+    pub(super) fn move_group(&mut self, group_id: u32, new_pos: Vector){
+        let members: Vec<u32> = self.movable_members(group_id).iter().map(|node| node.id).collect();
+        let offset = match members.first().and_then(|id| self.node(*id)) {
+            Some(anchor) => new_pos - anchor.get_pos(),
+            None => return,
+        };
+        if offset == Vector::new(0.0, 0.0) {
+            return;
+        }
+        for node in self.nodes.iter_mut().filter(|node| members.contains(&node.id)) {
+            node.set_new_pos(node.get_pos() + offset);
+        }
+        self.mark_dirty();
+    }
+
+    /// Recomputes the box of every group from the current member positions:
+    /// the padded bounding box when expanded, a fixed-size box centered on
+    /// the members when collapsed.
+    pub(super) fn fit_groups(&mut self){
+        if self.groups.is_empty() {
+            return;
+        }
+        let bounds: HashMap<u32, Rectangle> = self.nodes.iter()
+            .map(|node| (node.id, node.bounds))
+            .collect();
+        for group in self.groups.iter_mut() {
+            let mut members = group.member_ids.iter().filter_map(|id| bounds.get(id));
+            let first = match members.next() {
+                Some(first) => *first,
+                None => continue,
+            };
+            let (mut min, mut max) = (first.position(), Point::new(first.x + first.width, first.y + first.height));
+            for b in members {
+                min = Point::new(min.x.min(b.x), min.y.min(b.y));
+                max = Point::new(max.x.max(b.x + b.width), max.y.max(b.y + b.height));
+            }
+            group.bounds = if group.collapsed {
+                let size = Group::COLLAPSED_SIZE;
+                Rectangle{
+                    x: (min.x + max.x - size.width) / 2.0,
+                    y: (min.y + max.y - size.height) / 2.0,
+                    width: size.width,
+                    height: size.height,
+                }
+            } else {
+                Rectangle{
+                    x: min.x - Group::PADDING,
+                    y: min.y - Group::PADDING,
+                    width: max.x - min.x + 2.0 * Group::PADDING,
+                    height: max.y - min.y + 2.0 * Group::PADDING,
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use iced::{Point, Rectangle};
+use iced_graphics::canvas::Path;
+use iced_graphics::canvas::path::Builder;
+use serde::{Deserialize, Serialize};
+
+/// Glyphs from the built-in icon set that can be drawn inside a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Icon{
+    Circle,
+    Square,
+    Triangle,
+    Diamond,
+    Cross,
+    Star,
+}
+
+impl Icon{
+    /// Fraction of the node's smaller side taken up by the icon.
+    const RELATIVE_SIZE: f32 = 0.4;
+    const CIRCLE_SEGMENTS: usize = 32;
+
+    /// Square area, centered in `node_bounds`, that the icon is drawn into.
+    pub fn placement(node_bounds: Rectangle) -> Rectangle{
+        let size = node_bounds.width.min(node_bounds.height) * Self::RELATIVE_SIZE;
+        let center = node_bounds.center();
+        Rectangle{
+            x: center.x - size / 2.0,
+            y: center.y - size / 2.0,
+            width: size,
+            height: size,
+        }
+    }
+
+    /// Builds the path of the icon inside `area`.
+    pub fn path(&self, area: Rectangle) -> Path{
+        match self {
+            Icon::Circle => {
+                Path::circle(area.center(), area.width.min(area.height) / 2.0)
+            }
+            _ => polygon(&self.outline(area)),
+        }
+    }
+
+    /// Corners of the icon's outline inside `area`. Curved icons are
+    /// approximated with line segments.
+    pub fn outline(&self, area: Rectangle) -> Vec<Point>{
+        let center = area.center();
+        let half = area.width.min(area.height) / 2.0;
+        match self {
+            Icon::Circle => (0..Self::CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let angle = 2.0 * std::f32::consts::PI * i as f32
+                        / Self::CIRCLE_SEGMENTS as f32;
+                    Point::new(center.x + half * angle.cos(), center.y + half * angle.sin())
+                })
+                .collect(),
+            Icon::Square => vec![
+                Point::new(center.x - half, center.y - half),
+                Point::new(center.x + half, center.y - half),
+                Point::new(center.x + half, center.y + half),
+                Point::new(center.x - half, center.y + half),
+            ],
+            Icon::Triangle => vec![
+                Point::new(center.x, center.y - half),
+                Point::new(center.x + half, center.y + half),
+                Point::new(center.x - half, center.y + half),
+            ],
+            Icon::Diamond => vec![
+                Point::new(center.x, center.y - half),
+                Point::new(center.x + half, center.y),
+                Point::new(center.x, center.y + half),
+                Point::new(center.x - half, center.y),
+            ],
+            Icon::Cross => {
+                let arm = half / 3.0;
+                vec![
+                    Point::new(center.x - arm, center.y - half),
+                    Point::new(center.x + arm, center.y - half),
+                    Point::new(center.x + arm, center.y - arm),
+                    Point::new(center.x + half, center.y - arm),
+                    Point::new(center.x + half, center.y + arm),
+                    Point::new(center.x + arm, center.y + arm),
+                    Point::new(center.x + arm, center.y + half),
+                    Point::new(center.x - arm, center.y + half),
+                    Point::new(center.x - arm, center.y + arm),
+                    Point::new(center.x - half, center.y + arm),
+                    Point::new(center.x - half, center.y - arm),
+                    Point::new(center.x - arm, center.y - arm),
+                ]
+            }
+            Icon::Star => (0..10)
+                .map(|i| {
+                    let radius = if i % 2 == 0 { half } else { half * 0.4 };
+                    let angle = std::f32::consts::PI * i as f32 / 5.0
+                        - std::f32::consts::FRAC_PI_2;
+                    Point::new(
+                        center.x + radius * angle.cos(),
+                        center.y + radius * angle.sin(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+fn polygon(points: &[Point]) -> Path{
+    let mut builder = Builder::new();
+    if let Some((first, rest)) = points.split_first() {
+        builder.move_to(*first);
+        for point in rest {
+            builder.line_to(*point);
+        }
+        builder.close();
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests{
+    use iced::Rectangle;
+
+    use super::Icon;
+    use crate::network::Node;
+
+    #[test]
+    fn icon_is_a_centered_square_of_the_smaller_side(){
+        let node = Rectangle{ x: 10.0, y: 20.0, width: 200.0, height: 100.0 };
+        let area = Icon::placement(node);
+        assert_eq!(area, Rectangle{ x: 90.0, y: 50.0, width: 40.0, height: 40.0 });
+        assert_eq!(area.center(), node.center());
+    }
+
+    #[test]
+    fn every_icon_stays_inside_its_placement(){
+        let area = Icon::placement(Rectangle{ x: -50.0, y: 0.0, width: 80.0, height: 120.0 });
+        for icon in [Icon::Circle, Icon::Square, Icon::Triangle, Icon::Diamond, Icon::Cross, Icon::Star] {
+            for point in icon.outline(area) {
+                assert!(
+                    point.x >= area.x - 1e-3 && point.x <= area.x + area.width + 1e-3
+                        && point.y >= area.y - 1e-3 && point.y <= area.y + area.height + 1e-3,
+                    "{:?} reaches {:?} outside {:?}", icon, point, area,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nodes_draw_their_icon_at_its_placement(){
+        let bounds = Rectangle{ x: 10.0, y: 20.0, width: 200.0, height: 100.0 };
+        let mut node = Node::with_bounds(bounds);
+        assert_eq!(node.icon_placement(), None);
+        node.icon = Some(Icon::Star);
+        assert_eq!(node.icon_placement(), Some((Icon::Star, Icon::placement(bounds))));
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use iced::{Point, Vector};
+
+use super::Network;
+
+/// Cursor sample used to estimate the panning velocity.
+pub(super) struct PanSample{
+    pub(super) position: Point,
+    pub(super) time: Instant,
+}
+
+impl Network{
+    /// Fraction of the pan velocity lost per second once the drag ends.
+    const INERTIA_DECAY: f32 = 5.0;
+    /// Speed, in world units per second, below which inertia stops.
+    const INERTIA_MIN_SPEED: f32 = 5.0;
+    /// A release this long after the last cursor movement does not fling.
+    const INERTIA_RELEASE_WINDOW: Duration = Duration::from_millis(80);
+
+    /// Enables or disables momentum after a screen pan is released.
+    pub fn set_pan_inertia(&mut self, enabled: bool){
+        self.inertia_enabled = enabled;
+        if !enabled {
+            self.stop_inertia();
+        }
+    }
+
+    pub(super) fn stop_inertia(&mut self){
+        self.pan_velocity = Vector::new(0.0, 0.0);
+        self.pan_sample = None;
+    }
+
+    /// Updates the pan velocity estimate from a cursor move during a screen
+    /// pan, smoothing it over the last few samples.
+    pub(super) fn track_pan_velocity(&mut self, position: Point){
+        let now = Instant::now();
+        if let Some(sample) = &self.pan_sample {
+            let elapsed = now.duration_since(sample.time).as_secs_f32();
+            if elapsed > 0.0 {
+                let velocity = (position - sample.position) * (1.0 / (elapsed * self.scaling));
+                self.pan_velocity = self.pan_velocity * 0.5 + velocity * 0.5;
+            }
+        }
+        self.pan_sample = Some(PanSample{ position, time: now });
+    }
+
+    /// Keeps the velocity of a released screen pan only if the cursor was
+    /// still moving at release time.
+    pub(super) fn release_pan(&mut self){
+        let recent = self.pan_sample.as_ref()
+            .is_some_and(|sample| sample.time.elapsed() <= Self::INERTIA_RELEASE_WINDOW);
+        if !self.inertia_enabled || !recent {
+            self.pan_velocity = Vector::new(0.0, 0.0);
+        }
+        self.pan_sample = None;
+    }
+
+    /// Whether a released pan is still coasting.
+    pub(super) fn inertia_active(&self) -> bool{
+        let speed = (self.pan_velocity.x * self.pan_velocity.x
+            + self.pan_velocity.y * self.pan_velocity.y).sqrt();
+        self.pan_sample.is_none() && speed >= Self::INERTIA_MIN_SPEED
+    }
+
+    /// Advances the inertial pan by `elapsed`. Returns whether the view moved.
+    pub(super) fn tick_inertia(&mut self, elapsed: Duration) -> bool{
+        let speed = (self.pan_velocity.x * self.pan_velocity.x
+            + self.pan_velocity.y * self.pan_velocity.y).sqrt();
+        if self.pan_sample.is_some() || speed < Self::INERTIA_MIN_SPEED {
+            if self.pan_sample.is_none() {
+                self.pan_velocity = Vector::new(0.0, 0.0);
+            }
+            return false;
+        }
+        let seconds = elapsed.as_secs_f32();
+        self.translation = self.translation + self.pan_velocity * seconds;
+        self.pan_velocity = if self.clamp_translation() {
+            Vector::new(0.0, 0.0)
+        } else {
+            self.pan_velocity * (-Self::INERTIA_DECAY * seconds).exp()
+        };
+        true
+    }
+}
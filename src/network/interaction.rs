@@ -0,0 +1,534 @@
+//! Mouse handling as a state machine over `Interaction`. `Network::interpret`
+//! reads an event against the current state and the model and answers with
+//! the `Action`s it calls for, without changing anything; `Network::perform`
+//! then applies them one by one. Cursor moves during a drag are coalesced
+//! to one per frame. Keys are handled by `Network::handle_key`.
+
+use iced::{mouse, Point, Rectangle, Vector};
+use iced_graphics::canvas::{event, Cursor, Event};
+
+use super::{EdgeEnd, Guide, Network, NetworkMessage, Tool};
+
+/// What the mouse is doing on the canvas.
+pub(super) enum Interaction{
+    None,
+    PanningScreen { translation: Vector, start: Point },
+    PanningNode { node_id: u32, translation: Vector, start: Point },
+    /// `translation` is where the group's anchor was; see
+    /// `Network::group_anchor`.
+    PanningGroup { group_id: u32, translation: Vector, start: Point },
+    /// Measure tool waiting for its second click; `start` is in world space.
+    Measuring { start: Point },
+    /// Pen stroke in progress, the last of the annotations.
+    Drawing,
+    /// An end of the edge being dragged to another node.
+    ReconnectingEdge { edge_id: u32, end: EdgeEnd },
+}
+
+impl Interaction{
+    /// Where the cursor was when a drag began.
+    pub(super) fn start(&self) -> Option<Point>{
+        match self {
+            Interaction::None
+            | Interaction::Measuring { .. }
+            | Interaction::Drawing
+            | Interaction::ReconnectingEdge { .. } => None,
+            Interaction::PanningScreen { start, .. }
+            | Interaction::PanningNode { start, .. }
+            | Interaction::PanningGroup { start, .. } => Some(*start),
+        }
+    }
+}
+
+/// A single change to the network asked for by a mouse event.
+pub(super) enum Action{
+    /// Enters a new interaction, with the drag threshold not yet crossed.
+    Begin(Interaction),
+    /// Leaves the current interaction.
+    End,
+    /// Marks the drag threshold as crossed.
+    StartDrag,
+    StopInertia,
+    StopCameraAnimation,
+    StopLayoutAnimation,
+    UnselectNodes,
+    UnselectEdges,
+    UnselectGroups,
+    SelectNode { id: u32, selected: bool },
+    SelectEdge(u32),
+    /// Selects the given group and unselects all others.
+    SelectOnlyGroup(Option<u32>),
+    /// Sets the translation, within the pan bounds.
+    PanTo(Vector),
+    /// Pans by a screen offset.
+    PanBy(Vector),
+    ZoomAt { scaling: f32, focus: Point },
+    TrackPanVelocity(Point),
+    ReleasePan,
+    /// Moves the dragged node's top-left corner, and the rest of the
+    /// selection with it.
+    DragSelection { node_id: u32, to: Vector },
+    /// Replaces the alignment guides shown during a node drag.
+    ShowGuides(Vec<Guide>),
+    MoveGroup { group_id: u32, to: Vector },
+    MeasureClick(Point),
+    PlaceText(Point),
+    /// Creates a node from the palette template being dragged.
+    DropTemplate(Point),
+    /// Moves an end of an edge to the node, or leaves the edge as it was
+    /// if there is none.
+    ReconnectEdge { edge_id: u32, end: EdgeEnd, node_id: Option<u32> },
+    StartStroke(Point),
+    ExtendStroke(Point),
+    FinishStroke,
+}
+
+impl Network{
+    /// The actions a mouse event calls for in the current state, and
+    /// whether the canvas captures the event.
+    pub(super) fn interpret(&self, event: mouse::Event, bounds: Rectangle, cursor: Cursor) -> (event::Status, Vec<Action>){
+        if let mouse::Event::ButtonReleased(mouse::Button::Left) = event {
+            if let (Some(_), Some(position)) = (&self.template_drag, cursor.position_in(&bounds)) {
+                let world = self.screen_to_world(position, bounds.size());
+                return (event::Status::Captured, vec![Action::DropTemplate(world), Action::End]);
+            }
+        }
+        // Releases end drags even outside the canvas.
+        if let mouse::Event::ButtonReleased(_) = event {
+            return match self.interaction {
+                // Measurements are made with two clicks, not a drag.
+                Interaction::Measuring { .. } => (event::Status::Captured, Vec::new()),
+                Interaction::PanningScreen { .. } => (event::Status::Ignored, vec![Action::ReleasePan, Action::End]),
+                Interaction::Drawing => (event::Status::Ignored, vec![Action::FinishStroke, Action::End]),
+                Interaction::ReconnectingEdge { edge_id, end } => {
+                    let node_id = cursor.position_in(&bounds)
+                        .and_then(|position| self.get_node_at_screen(position, bounds.size()));
+                    (event::Status::Captured, vec![Action::ReconnectEdge{ edge_id, end, node_id }, Action::End])
+                }
+                _ => (event::Status::Ignored, vec![Action::End]),
+            };
+        }
+
+        let cursor_position = match cursor.position_in(&bounds) {
+            Some(position) => position,
+            None => return (event::Status::Ignored, Vec::new()),
+        };
+        let world_position = self.screen_to_world(cursor_position, bounds.size());
+
+        match event {
+            // The pan button pans whatever the tool and whatever else the
+            // button is for, and so does the left one while Space is held.
+            mouse::Event::ButtonPressed(button)
+                if button == self.pan_button || (button == mouse::Button::Left && self.space_held) =>
+            {
+                let mut actions = vec![Action::StopInertia, Action::StopCameraAnimation];
+                self.press_pan(cursor_position, &mut actions);
+                (event::Status::Captured, actions)
+            }
+            mouse::Event::ButtonPressed(mouse::Button::Left) if self.tool != Tool::Select => {
+                let action = match self.tool {
+                    Tool::Measure => Action::MeasureClick(world_position),
+                    Tool::Text => Action::PlaceText(world_position),
+                    _ => Action::StartStroke(world_position),
+                };
+                (event::Status::Captured, vec![action])
+            }
+            mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                let mut actions = vec![Action::StopInertia, Action::StopCameraAnimation];
+                if let Some((edge_id, end)) = self.edge_end_at(world_position) {
+                    actions.push(Action::Begin(Interaction::ReconnectingEdge{ edge_id, end }));
+                    return (event::Status::Captured, actions);
+                }
+                match self.get_node_at_screen(cursor_position, bounds.size()) {
+                    Some(id) => self.press_node(id, cursor_position, &mut actions),
+                    None => self.press_background(world_position, cursor_position, &mut actions),
+                }
+                (event::Status::Captured, actions)
+            }
+            mouse::Event::ButtonPressed(_) => {
+                (event::Status::Captured, vec![Action::StopInertia, Action::StopCameraAnimation])
+            }
+            mouse::Event::CursorMoved { .. } => {
+                let mut actions = Vec::new();
+                if let Some(start) = self.interaction.start().filter(|_| !self.drag_started) {
+                    if cursor_position.distance(start) < Self::DRAG_THRESHOLD {
+                        return (event::Status::Captured, actions);
+                    }
+                    actions.push(Action::StartDrag);
+                }
+                let dragged = |translation: Vector, start: Point| {
+                    translation + (cursor_position - start) * (1.0 / self.scaling)
+                };
+                match self.interaction {
+                    Interaction::None => return (event::Status::Ignored, actions),
+                    Interaction::PanningScreen { translation, start } => {
+                        actions.push(Action::TrackPanVelocity(cursor_position));
+                        actions.push(Action::PanTo(dragged(translation, start)));
+                    }
+                    Interaction::PanningNode { node_id, translation, start } => {
+                        if self.node(node_id).is_some_and(|node| node.pinned) {
+                            let to = translation + (cursor_position - start);
+                            actions.push(Action::DragSelection{ node_id, to });
+                        } else {
+                            let (to, guides) = self.snap_drag(node_id, dragged(translation, start), bounds.size());
+                            actions.push(Action::DragSelection{ node_id, to });
+                            actions.push(Action::ShowGuides(guides));
+                        }
+                    }
+                    Interaction::PanningGroup { group_id, translation, start } => {
+                        actions.push(Action::MoveGroup{ group_id, to: dragged(translation, start) });
+                    }
+                    Interaction::Drawing => actions.push(Action::ExtendStroke(world_position)),
+                    Interaction::Measuring { .. } | Interaction::ReconnectingEdge { .. } => {}
+                }
+                (event::Status::Captured, actions)
+            }
+            mouse::Event::WheelScrolled { delta } => {
+                // Mouse wheels report lines and zoom; trackpads report
+                // pixels and pan with two fingers. Shift pans sideways and
+                // Ctrl zooms, which is also how pinches arrive on some
+                // platforms.
+                let focus = cursor.position_from(bounds.center())
+                    .unwrap_or(Point::ORIGIN);
+                let action = match delta {
+                    mouse::ScrollDelta::Lines { x, y } if self.modifiers.shift() => {
+                        // Some platforms already turn Shift+wheel into a horizontal
+                        // scroll, others keep reporting it on the vertical axis.
+                        let amount = if x != 0.0 { x } else { y };
+                        Action::PanBy(Vector::new(amount * Self::SCROLL_LINE_HEIGHT, 0.0))
+                    }
+                    mouse::ScrollDelta::Lines { y, .. } => {
+                        Action::ZoomAt{ scaling: self.scaling * (1.0 + y / 30.0), focus }
+                    }
+                    mouse::ScrollDelta::Pixels { y, .. } if self.modifiers.control() => {
+                        let lines = y / Self::SCROLL_LINE_HEIGHT;
+                        Action::ZoomAt{ scaling: self.scaling * (1.0 + lines / 30.0), focus }
+                    }
+                    mouse::ScrollDelta::Pixels { x, y } if self.modifiers.shift() => {
+                        let amount = if x != 0.0 { x } else { y };
+                        Action::PanBy(Vector::new(amount, 0.0))
+                    }
+                    mouse::ScrollDelta::Pixels { x, y } => Action::PanBy(Vector::new(x, y)),
+                };
+                (event::Status::Captured, vec![Action::StopCameraAnimation, action])
+            }
+            _ => (event::Status::Ignored, Vec::new()),
+        }
+    }
+
+    /// Press of a pan button: starts dragging the view.
+    fn press_pan(&self, cursor_position: Point, actions: &mut Vec<Action>){
+        actions.push(Action::Begin(Interaction::PanningScreen {
+            translation: self.translation,
+            start: cursor_position,
+        }));
+        actions.push(Action::TrackPanVelocity(cursor_position));
+    }
+
+    /// Left press on a node. A plain click on an unselected node replaces
+    /// the selection; clicking a selected one keeps it so the whole
+    /// selection can be dragged. Shift toggles the node instead.
+    fn press_node(&self, id: u32, cursor_position: Point, actions: &mut Vec<Action>){
+        let node = match self.node(id) {
+            Some(node) => node,
+            None => return,
+        };
+        let toggle = self.modifiers.shift();
+        if !toggle && !node.is_selected {
+            actions.extend([Action::UnselectNodes, Action::UnselectEdges, Action::UnselectGroups]);
+        }
+        let selected = !(toggle && node.is_selected);
+        if selected && !node.locked {
+            actions.push(Action::Begin(Interaction::PanningNode {
+                node_id: id,
+                translation: node.get_pos(),
+                start: cursor_position,
+            }));
+            actions.push(Action::StopLayoutAnimation);
+        }
+        actions.push(Action::SelectNode{ id, selected });
+    }
+
+    /// Left press away from the nodes: selects the edge or else the group
+    /// under the cursor, and starts dragging the group.
+    fn press_background(&self, world_position: Point, cursor_position: Point, actions: &mut Vec<Action>){
+        actions.extend([Action::UnselectNodes, Action::UnselectEdges]);
+        let edge_id = self.edge_at_screen(world_position);
+        let group_id = match edge_id {
+            Some(_) => None,
+            None => self.group_at(world_position),
+        };
+        if let Some(id) = edge_id {
+            actions.push(Action::SelectEdge(id));
+        }
+        let anchor = group_id.and_then(|id| Some((id, self.group_anchor(id)?)));
+        if let Some((group_id, anchor)) = anchor {
+            actions.push(Action::Begin(Interaction::PanningGroup {
+                group_id,
+                translation: anchor.get_pos(),
+                start: cursor_position,
+            }));
+            actions.push(Action::StopLayoutAnimation);
+        }
+        actions.push(Action::SelectOnlyGroup(group_id));
+    }
+
+    /// Keeps a cursor move during a drag for later instead of handling it.
+    /// Moves arrive far more often than frames are drawn and only the
+    /// latest one matters, so it is handled once, when the view is next
+    /// built or before the next other event. The returned message makes
+    /// sure the host builds the view. Only drags that pan or move things
+    /// are coalesced: pen strokes need every point. Moves outside the
+    /// canvas aren't deferred either.
+    pub(super) fn defer_cursor_move(&mut self, event: &Event, bounds: Rectangle, cursor: Cursor) -> Option<(event::Status, Option<NetworkMessage>)>{
+        match event {
+            Event::Mouse(event @ mouse::Event::CursorMoved { .. })
+                if self.interaction.start().is_some() && cursor.is_over(&bounds) =>
+            {
+                self.pending_move = Some((*event, bounds, cursor));
+                Some((event::Status::Captured, Some(NetworkMessage::Update)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Handles the deferred cursor move, if any.
+    pub(super) fn flush_cursor_move(&mut self){
+        if let Some((event, bounds, cursor)) = self.pending_move.take() {
+            let view = (self.translation, self.scaling);
+            let (_, actions) = self.interpret(event, bounds, cursor);
+            for action in actions {
+                self.perform(action);
+            }
+            if view != (self.translation, self.scaling) {
+                self.notify_view_changed();
+            }
+        }
+    }
+
+    /// Applies an action from `interpret`.
+    pub(super) fn perform(&mut self, action: Action){
+        match action {
+            Action::Begin(interaction) => {
+                self.interaction = interaction;
+                self.drag_started = false;
+            }
+            Action::End => {
+                self.interaction = Interaction::None;
+                if !self.guides.is_empty() {
+                    self.guides.clear();
+                    self.request_redraw();
+                }
+            }
+            Action::StartDrag => self.drag_started = true,
+            Action::StopInertia => self.stop_inertia(),
+            Action::StopCameraAnimation => self.stop_camera_animation(),
+            Action::StopLayoutAnimation => self.stop_layout_animation(),
+            Action::UnselectNodes => self.unselect_all_nodes(),
+            Action::UnselectEdges => self.unselect_all_edges(),
+            Action::UnselectGroups => self.unselect_all_groups(),
+            Action::SelectNode{ id, selected } => {
+                if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+                    if node.is_selected != selected {
+                        node.set_selected(selected);
+                        self.groups_cache.clear();
+                    }
+                }
+            }
+            Action::SelectEdge(id) => {
+                if let Some(edge) = self.edges.iter_mut().find(|x| x.id == id) {
+                    if !edge.is_selected {
+                        edge.is_selected = true;
+                        self.groups_cache.clear();
+                    }
+                }
+            }
+            Action::SelectOnlyGroup(group_id) => {
+                for group in self.groups.iter_mut() {
+                    let selected = Some(group.id) == group_id;
+                    if group.is_selected != selected {
+                        group.is_selected = selected;
+                        self.groups_cache.clear();
+                    }
+                }
+            }
+            Action::PanTo(translation) => {
+                if translation != self.translation {
+                    self.translation = translation;
+                    self.clamp_translation();
+                    self.request_redraw();
+                }
+            }
+            Action::PanBy(offset) => self.pan_by(offset),
+            Action::ZoomAt{ scaling, focus } => {
+                self.zoom_at(scaling, focus);
+            }
+            Action::TrackPanVelocity(position) => self.track_pan_velocity(position),
+            Action::ReleasePan => self.release_pan(),
+            Action::DragSelection{ node_id, to } => {
+                if self.node(node_id).is_none() {
+                    println!("Could not pan node with id:{} because \
+                            the node could not be found in the network",
+                             node_id);
+                }
+                self.drag_selection(node_id, to);
+            }
+            Action::ShowGuides(guides) => self.guides = guides,
+            Action::MoveGroup{ group_id, to } => self.move_group(group_id, to),
+            Action::MeasureClick(world) => self.measure_click(world),
+            Action::PlaceText(world) => self.place_text(world),
+            Action::DropTemplate(world) => self.drop_template(world),
+            Action::ReconnectEdge{ edge_id, end, node_id } => {
+                if let Some(node_id) = node_id {
+                    if let Err(error) = self.reconnect_edge(edge_id, end, node_id) {
+                        println!("Could not reconnect edge {}: {}", edge_id, error);
+                    }
+                }
+                self.request_redraw();
+            }
+            Action::StartStroke(world) => self.start_stroke(world),
+            Action::ExtendStroke(world) => self.extend_stroke(world),
+            Action::FinishStroke => self.finish_stroke(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::sync::{Arc, Mutex};
+
+    use iced::{mouse, Point, Rectangle, Vector};
+    use iced_graphics::canvas::{Cursor, Event, Program};
+
+    use crate::network::{Network, NetworkMessage, Node};
+
+    const BOUNDS: Rectangle = Rectangle{ x: 0.0, y: 0.0, width: 800.0, height: 600.0 };
+
+    /// Feeds a mouse event at a canvas position through `interpret` and
+    /// `perform`, as the canvas does.
+    fn send(network: &mut Network, event: mouse::Event, position: Point){
+        let (_, actions) = network.interpret(event, BOUNDS, Cursor::Available(position));
+        for action in actions {
+            network.perform(action);
+        }
+    }
+
+    fn moved(network: &mut Network, position: Point){
+        send(network, mouse::Event::CursorMoved{ position }, position);
+    }
+
+    /// Network with nodes at the given world positions, 100 wide and high.
+    /// The default view puts the world origin at the canvas center.
+    fn network_with_nodes(positions: &[(f32, f32)]) -> (Network, Vec<u32>){
+        let mut network = Network::empty();
+        let ids = positions.iter()
+            .map(|&(x, y)| network.add_node(Node::with_bounds(Rectangle{ x, y, width: 100.0, height: 100.0 })))
+            .collect();
+        (network, ids)
+    }
+
+    #[test]
+    fn pans_and_zooms_report_the_new_view(){
+        let mut network = Network::empty();
+        let views = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&views);
+        network.set_on_view_changed(move |translation, scaling| seen.lock().unwrap().push((translation, scaling)));
+        let update = |network: &mut Network, event: mouse::Event, position: Point| {
+            network.update(Event::Mouse(event), BOUNDS, Cursor::Available(position));
+        };
+
+        update(&mut network, mouse::Event::ButtonPressed(mouse::Button::Middle), Point::new(400.0, 300.0));
+        update(&mut network, mouse::Event::CursorMoved{ position: Point::new(460.0, 280.0) }, Point::new(460.0, 280.0));
+        update(&mut network, mouse::Event::ButtonReleased(mouse::Button::Middle), Point::new(460.0, 280.0));
+        assert_eq!(views.lock().unwrap().as_slice(), [(Vector::new(60.0, -20.0), 1.0)]);
+
+        let wheel = mouse::Event::WheelScrolled{ delta: mouse::ScrollDelta::Lines{ x: 0.0, y: 3.0 } };
+        update(&mut network, wheel, Point::new(400.0, 300.0));
+        let views = views.lock().unwrap();
+        assert_eq!(views.len(), 2);
+        assert_eq!(views[1], network.view_state());
+        assert!((views[1].1 - 1.1).abs() < 1e-5, "scaling is {}", views[1].1);
+    }
+
+    #[test]
+    fn middle_drag_pans_the_view(){
+        let mut network = Network::empty();
+        send(&mut network, mouse::Event::ButtonPressed(mouse::Button::Middle), Point::new(400.0, 300.0));
+        moved(&mut network, Point::new(450.0, 320.0));
+        send(&mut network, mouse::Event::ButtonReleased(mouse::Button::Middle), Point::new(450.0, 320.0));
+        assert_eq!(network.view_state(), (Vector::new(50.0, 20.0), 1.0));
+        // The view isn't saved, so panning leaves the document unmodified.
+        assert!(!network.is_dirty());
+        assert_eq!(network.revision(), 0);
+    }
+
+    #[test]
+    fn left_drag_moves_the_node(){
+        let (mut network, ids) = network_with_nodes(&[(0.0, 0.0)]);
+        send(&mut network, mouse::Event::ButtonPressed(mouse::Button::Left), Point::new(450.0, 350.0));
+        moved(&mut network, Point::new(480.0, 410.0));
+        send(&mut network, mouse::Event::ButtonReleased(mouse::Button::Left), Point::new(480.0, 410.0));
+        assert_eq!(network.node(ids[0]).map(Node::bounds), Some(Rectangle{ x: 30.0, y: 60.0, width: 100.0, height: 100.0 }));
+        assert_eq!(network.view_state(), (Vector::new(0.0, 0.0), 1.0));
+    }
+
+    #[test]
+    fn clicks_select_one_node_and_the_background_clears(){
+        let (mut network, ids) = network_with_nodes(&[(0.0, 0.0), (-300.0, -200.0)]);
+        let click = |network: &mut Network, position: Point| {
+            send(network, mouse::Event::ButtonPressed(mouse::Button::Left), position);
+            send(network, mouse::Event::ButtonReleased(mouse::Button::Left), position);
+        };
+        click(&mut network, Point::new(450.0, 350.0));
+        assert_eq!(network.selected_node_ids(), [ids[0]]);
+        click(&mut network, Point::new(150.0, 150.0));
+        assert_eq!(network.selected_node_ids(), [ids[1]]);
+        click(&mut network, Point::new(700.0, 100.0));
+        assert!(network.selected_node_ids().is_empty());
+    }
+
+    #[test]
+    fn wheel_zooms_around_the_cursor(){
+        let mut network = Network::empty();
+        let cursor = Point::new(600.0, 200.0);
+        let before = network.screen_to_world(cursor, BOUNDS.size());
+        send(&mut network, mouse::Event::WheelScrolled{ delta: mouse::ScrollDelta::Lines{ x: 0.0, y: 3.0 } }, cursor);
+        let (_, scaling) = network.view_state();
+        assert!((scaling - 1.1).abs() < 1e-5, "scaling is {}", scaling);
+        let after = network.screen_to_world(cursor, BOUNDS.size());
+        assert!(before.distance(after) < 1e-3, "{:?} moved to {:?}", before, after);
+        assert_eq!(network.revision(), 0);
+    }
+
+    #[test]
+    fn dropping_a_selection_reports_every_moved_node(){
+        let (mut network, ids) = network_with_nodes(&[(0.0, 0.0), (-300.0, -200.0)]);
+        network.select_all();
+        let mut update = |event: mouse::Event, position: Point| {
+            network.update(Event::Mouse(event), BOUNDS, Cursor::Available(position)).1
+        };
+        update(mouse::Event::ButtonPressed(mouse::Button::Left), Point::new(450.0, 350.0));
+        update(mouse::Event::CursorMoved{ position: Point::new(480.0, 410.0) }, Point::new(480.0, 410.0));
+        let messages = match update(mouse::Event::ButtonReleased(mouse::Button::Left), Point::new(480.0, 410.0)) {
+            Some(NetworkMessage::Batch(messages)) => messages,
+            message => panic!("expected a batch, got {:?}", message),
+        };
+        let moved: Vec<_> = messages.iter()
+            .filter_map(|message| match message {
+                NetworkMessage::NodeMoved{ id, x, y } => Some((*id, *x, *y)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(moved, [(ids[0], 30.0, 60.0), (ids[1], -270.0, -140.0)]);
+    }
+
+    #[test]
+    fn two_finger_scroll_pans_without_modifying_the_document(){
+        let mut network = Network::empty();
+        let cursor = Point::new(400.0, 300.0);
+        send(&mut network, mouse::Event::WheelScrolled{ delta: mouse::ScrollDelta::Pixels{ x: 30.0, y: -10.0 } }, cursor);
+        assert_ne!(network.view_state().0, Vector::new(0.0, 0.0));
+        assert!(!network.is_dirty());
+        assert_eq!(network.revision(), 0);
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::{Network, SerNetwork};
+
+/// Error produced when a graph cannot be fetched or read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError{
+    /// The document could not be downloaded.
+    Fetch(String),
+    /// The document could not be read from disk.
+    Read(String),
+    /// The document is not a valid graph.
+    Parse(String),
+}
+
+impl fmt::Display for LoadError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Fetch(message) => write!(f, "could not fetch the graph: {}", message),
+            LoadError::Read(message) => write!(f, "could not open the graph: {}", message),
+            LoadError::Parse(message) => write!(f, "could not read the graph: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError{}
+
+impl Network{
+    /// Builds a network from a JSON document of the form
+    /// `{"nodes": [{"id": 1, "label": "a", "x": 0, "y": 0}], "edges": [{"from": 1, "to": 2}]}`.
+    /// Everything but node ids and positions is optional; see `SerNetwork`
+    /// for the full format. Ids in the document are kept.
+    pub fn import_json(src: &str) -> Result<Network, LoadError>{
+        let graph: SerNetwork = serde_json::from_str(src)
+            .map_err(|error| LoadError::Parse(error.to_string()))?;
+        Network::try_from(graph)
+    }
+
+    /// Writes the network in the format read by `import_json`.
+    pub fn export_json(&self) -> String{
+        serde_json::to_string_pretty(&SerNetwork::from(self))
+            .expect("a network always serializes")
+    }
+
+    /// Writes a snapshot taken with `SerNetwork::from` to `path`. Serializing
+    /// a big graph takes a while, so this is meant to run in a `Command`
+    /// rather than on the UI thread.
+    pub async fn write_json(graph: SerNetwork, path: PathBuf) -> Result<(), String>{
+        let src = serde_json::to_string(&graph).map_err(|error| error.to_string())?;
+        tokio::fs::write(&path, src).await
+            .map_err(|error| format!("could not write {}: {}", path.display(), error))
+    }
+
+    /// Reads back an autosave written with `write_json` for the network
+    /// saved at `file`, or for an untitled one. The changes in it were
+    /// never saved, so the network starts out modified.
+    pub fn load_recovery(path: &Path, file: Option<PathBuf>) -> Result<Network, LoadError>{
+        let src = std::fs::read_to_string(path)
+            .map_err(|error| LoadError::Read(error.to_string()))?;
+        let mut network = Network::import_json(&src)?;
+        network.current_path = file;
+        network.dirty = true;
+        Ok(network)
+    }
+
+    /// The recovery file of this network if it holds changes that were
+    /// never saved: it was written after the network's file, or the
+    /// network has no file. Left behind when the app didn't exit cleanly.
+    pub fn unsaved_recovery(&self) -> Option<PathBuf>{
+        let recovery = self.recovery_path();
+        let written = std::fs::metadata(&recovery).and_then(|metadata| metadata.modified()).ok()?;
+        let saved = self.current_path.as_ref()
+            .and_then(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok());
+        match saved {
+            Some(saved) if saved >= written => None,
+            _ => Some(recovery),
+        }
+    }
+
+    /// Opens a JSON graph (see `import_json`) saved at `path`, which becomes
+    /// the network's file.
+    pub async fn read_json(path: PathBuf) -> Result<Network, LoadError>{
+        let src = tokio::fs::read_to_string(&path).await
+            .map_err(|error| LoadError::Read(format!("{}: {}", path.display(), error)))?;
+        let mut network = Network::import_json(&src)?;
+        let revision = network.revision;
+        network.mark_saved(path, revision);
+        Ok(network)
+    }
+
+    /// Downloads a JSON graph (see `import_json`) from `url`.
+    pub async fn fetch_json(url: String) -> Result<Network, LoadError>{
+        let response = reqwest::get(&url).await
+            .and_then(|response| response.error_for_status())
+            .map_err(|error| LoadError::Fetch(error.to_string()))?;
+        let body = response.text().await
+            .map_err(|error| LoadError::Fetch(error.to_string()))?;
+        Network::import_json(&body)
+    }
+}
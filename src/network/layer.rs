@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Edge, Network};
+
+/// Named set of nodes and edges that are shown or locked together. Every
+/// node and edge is on exactly one layer, by name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layer{
+    pub name: String,
+    #[serde(default = "visible")]
+    pub visible: bool,
+    /// Items on a locked layer are drawn but can't be clicked or selected.
+    #[serde(default)]
+    pub locked: bool,
+}
+
+fn visible() -> bool{
+    true
+}
+
+impl Layer{
+    /// Layer of new nodes and edges, and of those saved before layers
+    /// existed. Networks always have it.
+    pub const DEFAULT: &'static str = "Default";
+
+    pub fn new(name: impl Into<String>) -> Self{
+        Layer{ name: name.into(), visible: true, locked: false }
+    }
+}
+
+impl Network{
+    pub fn layers(&self) -> &[Layer]{
+        &self.layers
+    }
+
+    /// Adds an empty layer on top of the others. Returns `false` if the
+    /// name is blank or already taken.
+    pub fn add_layer(&mut self, name: &str) -> bool{
+        let name = name.trim();
+        if name.is_empty() || self.layer(name).is_some() {
+            return false;
+        }
+        self.layers.push(Layer::new(name));
+        self.mark_dirty();
+        true
+    }
+
+    /// Shows or hides the nodes and edges on a layer. Hidden items are
+    /// unselected.
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool){
+        if let Some(layer) = self.layers.iter_mut().find(|layer| layer.name == name) {
+            layer.visible = visible;
+            self.unselect_unavailable();
+            self.mark_dirty();
+        }
+    }
+
+    /// Locks or unlocks the nodes and edges on a layer. Locked items are
+    /// unselected.
+    pub fn set_layer_locked(&mut self, name: &str, locked: bool){
+        if let Some(layer) = self.layers.iter_mut().find(|layer| layer.name == name) {
+            layer.locked = locked;
+            self.unselect_unavailable();
+            self.mark_dirty();
+        }
+    }
+
+    /// Puts the selected nodes and edges on the given layer, which must
+    /// exist. Returns `false` if it doesn't.
+    pub fn move_selection_to_layer(&mut self, name: &str) -> bool{
+        if self.layer(name).is_none() {
+            return false;
+        }
+        for node in self.nodes.iter_mut().filter(|node| node.is_selected) {
+            node.layer = name.to_string();
+        }
+        for edge in self.edges.iter_mut().filter(|edge| edge.is_selected) {
+            edge.layer = name.to_string();
+        }
+        self.unselect_unavailable();
+        self.mark_dirty();
+        true
+    }
+
+    fn layer(&self, name: &str) -> Option<&Layer>{
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Adds the layer if it is missing, e.g. when a document refers to a
+    /// layer it doesn't list.
+    pub(super) fn ensure_layer(&mut self, name: &str){
+        if self.layer(name).is_none() {
+            self.layers.push(Layer::new(name));
+        }
+    }
+
+    /// Whether items on the layer are drawn. Unknown layers are.
+    pub(super) fn is_layer_visible(&self, name: &str) -> bool{
+        self.layer(name).is_none_or(|layer| layer.visible)
+    }
+
+    /// Whether items on the layer can be clicked and selected: it is
+    /// visible and not locked.
+    pub(super) fn is_layer_available(&self, name: &str) -> bool{
+        self.layer(name).is_none_or(|layer| layer.visible && !layer.locked)
+    }
+
+    /// Whether the edge is drawn: its own layer and those of both its ends
+    /// are visible.
+    pub(super) fn is_edge_visible(&self, edge: &Edge) -> bool{
+        self.is_layer_visible(&edge.layer)
+            && [edge.from, edge.to].iter()
+                .all(|id| self.node(*id).is_none_or(|node| self.is_layer_visible(&node.layer)))
+    }
+
+    /// Unselects the nodes and edges on hidden or locked layers.
+    pub(super) fn unselect_unavailable(&mut self){
+        let nodes: Vec<bool> = self.nodes.iter().map(|node| self.is_layer_available(&node.layer)).collect();
+        for (node, available) in self.nodes.iter_mut().zip(nodes) {
+            if node.is_selected && !available {
+                node.set_selected(false);
+            }
+        }
+        let edges: Vec<bool> = self.edges.iter()
+            .map(|edge| self.is_layer_available(&edge.layer) && self.is_edge_visible(edge))
+            .collect();
+        for (edge, available) in self.edges.iter_mut().zip(edges) {
+            if edge.is_selected && !available {
+                edge.is_selected = false;
+            }
+        }
+        self.request_redraw();
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use iced::Vector;
+
+use super::{Axis, Network};
+
+impl Network{
+    /// Ideal distance between connected node centers in the force layout.
+    const FORCE_LAYOUT_DISTANCE: f32 = 200.0;
+    /// Layout stops early once no node moves more than this per iteration.
+    const FORCE_LAYOUT_MIN_MOVEMENT: f32 = 0.5;
+
+    /// Arranges the nodes with a Fruchterman-Reingold force-directed layout:
+    /// every pair of nodes repels, every edge attracts its endpoints. Runs for
+    /// at most `iterations` steps, stopping early once the layout settles.
+    /// Locked and pinned nodes push and pull the others but stay where they
+    /// are.
+    pub fn apply_force_layout(&mut self, iterations: usize){
+        let count = self.nodes.len();
+        if count < 2 {
+            return;
+        }
+        let k = Self::FORCE_LAYOUT_DISTANCE;
+        let index: HashMap<u32, usize> = self.nodes.iter()
+            .enumerate()
+            .map(|(i, node)| (node.id, i))
+            .collect();
+        let edges: Vec<(usize, usize)> = self.edges.iter()
+            .filter_map(|edge| Some((*index.get(&edge.from)?, *index.get(&edge.to)?)))
+            .filter(|(from, to)| from != to)
+            .collect();
+        let mut positions: Vec<Vector> = self.nodes.iter()
+            .map(|node| {
+                let center = node.bounds.center();
+                Vector::new(center.x, center.y)
+            })
+            .collect();
+        let locked: Vec<bool> = self.nodes.iter().map(|node| node.is_fixed()).collect();
+
+        let mut temperature = k * (count as f32).sqrt();
+        let cooling = temperature / (iterations.max(1) as f32 + 1.0);
+        for _ in 0..iterations {
+            let mut displacement = vec![Vector::new(0., 0.); count];
+            for i in 0..count {
+                for j in (i + 1)..count {
+                    let (delta, distance) = separation(positions[i], positions[j], i, j);
+                    let force = k * k / distance;
+                    let push = delta * (force / distance);
+                    displacement[i] = displacement[i] + push;
+                    displacement[j] = displacement[j] - push;
+                }
+            }
+            for &(from, to) in &edges {
+                let (delta, distance) = separation(positions[from], positions[to], from, to);
+                let force = distance * distance / k;
+                let pull = delta * (force / distance);
+                displacement[from] = displacement[from] - pull;
+                displacement[to] = displacement[to] + pull;
+            }
+
+            let mut max_movement: f32 = 0.0;
+            for ((position, offset), locked) in positions.iter_mut().zip(displacement).zip(&locked) {
+                if *locked {
+                    continue;
+                }
+                let length = (offset.x * offset.x + offset.y * offset.y).sqrt();
+                if length > 0.0 {
+                    let step = length.min(temperature);
+                    *position = *position + offset * (step / length);
+                    max_movement = max_movement.max(step);
+                }
+            }
+            temperature = (temperature - cooling).max(Self::FORCE_LAYOUT_MIN_MOVEMENT);
+            if max_movement < Self::FORCE_LAYOUT_MIN_MOVEMENT {
+                break;
+            }
+        }
+
+        let targets = self.nodes.iter()
+            .zip(positions)
+            .filter(|(node, _)| !node.is_fixed())
+            .map(|(node, center)| (node.id, Vector::new(
+                center.x - node.bounds.width / 2.0,
+                center.y - node.bounds.height / 2.0,
+            )))
+            .collect();
+        self.move_nodes_to(targets);
+    }
+}
+
+impl Network{
+    /// Horizontal gap between the columns of the layered layout.
+    const LAYERED_COLUMN_GAP: f32 = 120.0;
+    /// Vertical gap between the nodes of a column.
+    const LAYERED_ROW_GAP: f32 = 40.0;
+
+    /// Arranges the nodes in columns by the length of the longest path
+    /// leading to them, so every edge points to the right. Nodes within a
+    /// column are stacked top to bottom. Cycles are broken by ignoring
+    /// the edges that close them, so cyclic graphs still get a layout.
+    /// Locked and pinned nodes keep their place.
+    pub fn apply_layered_layout(&mut self){
+        if self.nodes.is_empty() {
+            return;
+        }
+        let depths = self.layer_depths();
+        let layer_count = depths.values().copied().max().unwrap_or(0) + 1;
+        let column_width = self.nodes.iter().map(|node| node.bounds.width).fold(0.0, f32::max)
+            + Self::LAYERED_COLUMN_GAP;
+        let left = self.nodes.iter().map(|node| node.bounds.x).fold(f32::INFINITY, f32::min);
+        let top = self.nodes.iter().map(|node| node.bounds.y).fold(f32::INFINITY, f32::min);
+
+        let mut heights = vec![0.0; layer_count];
+        for node in &self.nodes {
+            heights[depths[&node.id]] += node.bounds.height + Self::LAYERED_ROW_GAP;
+        }
+        let tallest = heights.iter().copied().fold(0.0, f32::max);
+        // Columns are centered vertically on the tallest one.
+        let mut cursors: Vec<f32> = heights.iter().map(|height| top + (tallest - height) / 2.0).collect();
+        let mut targets = Vec::new();
+        for node in &self.nodes {
+            let depth = depths[&node.id];
+            let position = Vector::new(left + depth as f32 * column_width, cursors[depth]);
+            cursors[depth] += node.bounds.height + Self::LAYERED_ROW_GAP;
+            if !node.is_fixed() {
+                targets.push((node.id, position));
+            }
+        }
+        self.move_nodes_to(targets);
+    }
+
+    /// Longest-path depth of every node, following edge direction. Nodes
+    /// are taken in topological order; when only cycles are left, the
+    /// remaining node with the fewest pending incoming edges goes next and
+    /// those edges are ignored.
+    fn layer_depths(&self) -> HashMap<u32, usize>{
+        let adjacency = self.adjacency();
+        let mut pending: HashMap<u32, usize> = self.nodes.iter().map(|node| (node.id, 0)).collect();
+        for targets in adjacency.values() {
+            for target in targets {
+                if let Some(count) = pending.get_mut(target) {
+                    *count += 1;
+                }
+            }
+        }
+
+        let mut depths: HashMap<u32, usize> = HashMap::new();
+        let mut remaining: Vec<u32> = self.nodes.iter().map(|node| node.id).collect();
+        while !remaining.is_empty() {
+            let next = remaining.iter()
+                .position(|id| pending[id] == 0)
+                .unwrap_or_else(|| {
+                    let fewest = remaining.iter().map(|id| pending[id]).min().unwrap_or(0);
+                    remaining.iter().position(|id| pending[id] == fewest).unwrap_or(0)
+                });
+            let id = remaining.remove(next);
+            let depth = *depths.entry(id).or_insert(0);
+            for &target in adjacency.get(&id).into_iter().flatten() {
+                if !remaining.contains(&target) {
+                    continue;
+                }
+                if let Some(count) = pending.get_mut(&target) {
+                    *count = count.saturating_sub(1);
+                }
+                let target_depth = depths.entry(target).or_insert(0);
+                *target_depth = (*target_depth).max(depth + 1);
+            }
+        }
+        depths
+    }
+}
+
+impl Network{
+    /// Arranges the nodes evenly around a circle centered on the world
+    /// origin, in node order, starting on the right and going clockwise on
+    /// screen. The circle grows with the number of nodes so they don't
+    /// overlap. Locked and pinned nodes keep their place.
+    pub fn apply_circular_layout(&mut self){
+        let nodes: Vec<_> = self.nodes.iter().filter(|node| !node.is_fixed()).collect();
+        let count = nodes.len();
+        if count == 0 {
+            return;
+        }
+        let largest = nodes.iter()
+            .map(|node| node.bounds.width.max(node.bounds.height))
+            .fold(0.0, f32::max);
+        let radius = (count as f32 * largest * 1.5 / (2.0 * std::f32::consts::PI))
+            .max(largest * 2.0);
+        let targets = nodes.iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let angle = 2.0 * std::f32::consts::PI * i as f32 / count as f32;
+                (node.id, Vector::new(
+                    radius * angle.cos() - node.bounds.width / 2.0,
+                    radius * angle.sin() - node.bounds.height / 2.0,
+                ))
+            })
+            .collect();
+        self.move_nodes_to(targets);
+    }
+}
+
+impl Network{
+    /// Arranges the nodes in a grid of ceil(sqrt(n)) columns, ordered by id
+    /// row by row, from the current top-left corner of the graph. Cells fit
+    /// the largest node, with `spacing` between them. Locked and pinned
+    /// nodes keep their place.
+    pub fn apply_grid_layout(&mut self, spacing: f32){
+        let mut nodes: Vec<_> = self.nodes.iter().filter(|node| !node.is_fixed()).collect();
+        if nodes.is_empty() {
+            return;
+        }
+        nodes.sort_by_key(|node| node.id);
+        let columns = (nodes.len() as f32).sqrt().ceil() as usize;
+        let cell_width = nodes.iter().map(|node| node.bounds.width).fold(0.0, f32::max) + spacing;
+        let cell_height = nodes.iter().map(|node| node.bounds.height).fold(0.0, f32::max) + spacing;
+        let left = nodes.iter().map(|node| node.bounds.x).fold(f32::INFINITY, f32::min);
+        let top = nodes.iter().map(|node| node.bounds.y).fold(f32::INFINITY, f32::min);
+        let targets = nodes.iter()
+            .enumerate()
+            .map(|(i, node)| (node.id, Vector::new(
+                left + (i % columns) as f32 * cell_width,
+                top + (i / columns) as f32 * cell_height,
+            )))
+            .collect();
+        self.move_nodes_to(targets);
+    }
+}
+
+/// Vector from `b` to `a` and its length. Coincident nodes get a small
+/// deterministic offset so they can push each other apart.
+fn separation(a: Vector, b: Vector, i: usize, j: usize) -> (Vector, f32){
+    let mut delta = a - b;
+    let mut distance = (delta.x * delta.x + delta.y * delta.y).sqrt();
+    if distance < 0.01 {
+        let angle = (i * 31 + j * 17) as f32;
+        delta = Vector::new(angle.cos(), angle.sin()) * 0.01;
+        distance = 0.01;
+    }
+    (delta, distance)
+}
+
+/// Which edge or center line `Network::align_selected` lines nodes up on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignKind{
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// Same center x, i.e. stacked along a vertical line.
+    HorizontalCenter,
+    /// Same center y, i.e. lined up along a horizontal line.
+    VerticalCenter,
+}
+
+impl Network{
+    /// Lines up the selected nodes: edges align to the outermost selected
+    /// node on that side, centers align to the middle of the selection.
+    /// Locked and pinned nodes are left out.
+    pub fn align_selected(&mut self, kind: AlignKind){
+        let selected: Vec<_> = self.nodes.iter()
+            .filter(|n| n.is_selected && !n.is_fixed())
+            .map(|n| n.bounds)
+            .collect();
+        if selected.len() < 2 {
+            return;
+        }
+        let left = selected.iter().map(|b| b.x).fold(f32::INFINITY, f32::min);
+        let right = selected.iter().map(|b| b.x + b.width).fold(f32::NEG_INFINITY, f32::max);
+        let top = selected.iter().map(|b| b.y).fold(f32::INFINITY, f32::min);
+        let bottom = selected.iter().map(|b| b.y + b.height).fold(f32::NEG_INFINITY, f32::max);
+        for node in self.nodes.iter_mut().filter(|n| n.is_selected && !n.is_fixed()) {
+            let bounds = node.bounds;
+            let position = match kind {
+                AlignKind::Left => Vector::new(left, bounds.y),
+                AlignKind::Right => Vector::new(right - bounds.width, bounds.y),
+                AlignKind::Top => Vector::new(bounds.x, top),
+                AlignKind::Bottom => Vector::new(bounds.x, bottom - bounds.height),
+                AlignKind::HorizontalCenter => {
+                    Vector::new((left + right) / 2.0 - bounds.width / 2.0, bounds.y)
+                }
+                AlignKind::VerticalCenter => {
+                    Vector::new(bounds.x, (top + bottom) / 2.0 - bounds.height / 2.0)
+                }
+            };
+            node.set_new_pos(position);
+        }
+        self.mark_dirty();
+    }
+}
+
+impl Network{
+    /// Spaces three or more selected nodes evenly along `axis`, keeping the
+    /// two outermost nodes in place so the gaps between neighbours are equal.
+    /// Locked and pinned nodes are left out.
+    pub fn distribute_selected(&mut self, axis: Axis){
+        let mut selected: Vec<(usize, f32, f32)> = self.nodes.iter()
+            .enumerate()
+            .filter(|(_, n)| n.is_selected && !n.is_fixed())
+            .map(|(i, n)| match axis {
+                Axis::Horizontal => (i, n.bounds.x, n.bounds.width),
+                Axis::Vertical => (i, n.bounds.y, n.bounds.height),
+            })
+            .collect();
+        if selected.len() < 3 {
+            return;
+        }
+        selected.sort_by(|a, b| (a.1 + a.2 / 2.0).total_cmp(&(b.1 + b.2 / 2.0)));
+        let (_, start, _) = selected[0];
+        let (_, last, last_size) = selected[selected.len() - 1];
+        let occupied: f32 = selected.iter().map(|(_, _, size)| size).sum();
+        let gap = (last + last_size - start - occupied) / (selected.len() - 1) as f32;
+
+        let mut cursor = start;
+        for (i, _, size) in selected {
+            let node = &mut self.nodes[i];
+            let position = match axis {
+                Axis::Horizontal => Vector::new(cursor, node.bounds.y),
+                Axis::Vertical => Vector::new(node.bounds.x, cursor),
+            };
+            node.set_new_pos(position);
+            cursor += size + gap;
+        }
+        self.mark_dirty();
+    }
+}
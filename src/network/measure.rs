@@ -0,0 +1,79 @@
+use iced::{alignment, Point, Rectangle, Size, Vector};
+use iced_graphics::canvas::{Cursor, Frame, Path, Stroke, Text};
+
+use super::{Interaction, Network};
+
+impl Network{
+    /// Length of the ticks across the ends of a dimension line, in pixels.
+    const MEASURE_TICK: f32 = 10.0;
+    const MEASURE_LABEL_SIZE: f32 = 14.0;
+
+    /// Handles a click at the world position `world` with the measure tool:
+    /// the first click starts a measurement and the second one ends it.
+    pub(super) fn measure_click(&mut self, world: Point){
+        match self.interaction {
+            Interaction::Measuring{ start } => {
+                self.measurement = Some((start, world));
+                self.interaction = Interaction::None;
+            }
+            _ => {
+                self.measurement = None;
+                self.interaction = Interaction::Measuring{ start: world };
+            }
+        }
+    }
+
+    /// Draws the finished measurement, or the one in progress up to the
+    /// cursor, in screen space.
+    pub(super) fn draw_measurement(&self, frame: &mut Frame, bounds: Rectangle, cursor: Cursor){
+        let (start, end) = match self.interaction {
+            Interaction::Measuring{ start } => match cursor.position_in(&bounds) {
+                Some(position) => (start, self.screen_to_world(position, bounds.size())),
+                None => return,
+            },
+            _ => match self.measurement {
+                Some(measurement) => measurement,
+                None => return,
+            },
+        };
+        let size = bounds.size();
+        let (a, b) = (self.world_to_screen(start, size), self.world_to_screen(end, size));
+        let color = self.theme.selection;
+        let stroke = Stroke{ color, width: 1.5, ..Stroke::default() };
+        frame.stroke(&Path::line(a, b), stroke);
+
+        let direction = b - a;
+        let length = a.distance(b);
+        if length > 0.0 {
+            let normal = Vector::new(-direction.y, direction.x) * (Self::MEASURE_TICK / 2.0 / length);
+            for end in [a, b] {
+                frame.stroke(&Path::line(end - normal, end + normal), stroke);
+            }
+        }
+
+        // Angles count counterclockwise from the positive x axis, as on
+        // paper, even though y grows downwards on screen.
+        let delta = end - start;
+        let distance = start.distance(end);
+        let angle = (-delta.y).atan2(delta.x).to_degrees();
+        let content = format!("{:.1}  {:.1}°", distance, angle);
+        let center = Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+        let padding = 4.0;
+        // Glyph metrics aren't available here, so estimate the width.
+        let text_size = Size::new(content.chars().count() as f32 * Self::MEASURE_LABEL_SIZE * 0.6, Self::MEASURE_LABEL_SIZE);
+        let background = Path::rectangle(
+            Point::new(center.x - text_size.width / 2.0 - padding, center.y - text_size.height / 2.0 - padding),
+            Size::new(text_size.width + 2.0 * padding, text_size.height + 2.0 * padding),
+        );
+        frame.fill(&background, self.theme.backdrop());
+        frame.fill_text(Text{
+            content,
+            position: center,
+            color,
+            size: Self::MEASURE_LABEL_SIZE,
+            horizontal_alignment: alignment::Horizontal::Center,
+            vertical_alignment: alignment::Vertical::Center,
+            ..Text::default()
+        });
+    }
+}
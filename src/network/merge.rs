@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use iced::Vector;
+
+use super::Network;
+
+impl Network{
+    /// Room left between the existing nodes and merged ones.
+    const MERGE_GAP: f32 = 100.0;
+
+    /// Adds the nodes, edges, groups and notes of `other` to the network.
+    /// They get fresh ids, so nothing collides with what is already there,
+    /// and are moved to the right of the existing nodes. The merged nodes
+    /// end up as the selection; their new ids are returned.
+    pub fn merge(&mut self, other: Network) -> Vec<u32>{
+        let offset = self.merge_offset(&other);
+        let mut ids = HashMap::new();
+        let mut remap = |network: &mut Network, id: u32| *ids.entry(id).or_insert_with(|| network.allocate_id());
+
+        self.unselect_all_nodes();
+        self.unselect_all_edges();
+        self.unselect_all_groups();
+        let mut merged = Vec::with_capacity(other.nodes.len());
+        for mut node in other.nodes {
+            node.id = remap(self, node.id);
+            if !node.pinned {
+                node.set_new_pos(node.get_pos() + offset);
+            }
+            node.is_selected = true;
+            self.ensure_layer(&node.layer);
+            merged.push(node.id);
+            self.nodes.push(node);
+        }
+        for mut edge in other.edges {
+            edge.id = remap(self, edge.id);
+            edge.from = remap(self, edge.from);
+            edge.to = remap(self, edge.to);
+            edge.is_selected = false;
+            self.ensure_layer(&edge.layer);
+            self.edges.push(edge);
+        }
+        for mut group in other.groups {
+            group.id = remap(self, group.id);
+            group.member_ids = group.member_ids.iter().map(|id| remap(self, *id)).collect();
+            group.is_selected = false;
+            self.groups.push(group);
+        }
+        for mut text in other.texts {
+            text.id = remap(self, text.id);
+            text.position = text.position + offset;
+            self.texts.push(text);
+        }
+        for mut annotation in other.annotations {
+            for point in annotation.points.iter_mut() {
+                *point = *point + offset;
+            }
+            self.annotations.push(annotation);
+        }
+        self.mark_dirty();
+        merged
+    }
+
+    /// How far to move the nodes of `other` so their bounding box starts
+    /// right of the existing nodes, top edges aligned.
+    fn merge_offset(&self, other: &Network) -> Vector{
+        let bounds = |network: &Network| Self::extent(network.nodes.iter().filter(|node| !node.pinned));
+        match (bounds(self), bounds(other)) {
+            (Some(existing), Some(merged)) => Vector::new(
+                existing.x + existing.width + Self::MERGE_GAP - merged.x,
+                existing.y - merged.y,
+            ),
+            _ => Vector::new(0.0, 0.0),
+        }
+    }
+}
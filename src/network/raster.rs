@@ -0,0 +1,141 @@
+use ab_glyph::{point, Font, FontRef, Glyph, PxScale, ScaleFont};
+use iced::{Color, Point, Vector};
+use image::{Rgba, RgbaImage};
+
+/// Minimal software rasterizer used to export the canvas to a bitmap.
+/// Shapes are given in image pixel coordinates.
+pub struct Raster{
+    pub image: RgbaImage,
+}
+
+impl Raster{
+    pub fn new(width: u32, height: u32, background: Color) -> Self{
+        Raster{
+            image: RgbaImage::from_pixel(width, height, to_rgba(background)),
+        }
+    }
+
+    /// Fills a polygon using the even-odd rule, sampling at pixel centers.
+    pub fn fill_polygon(&mut self, points: &[Point], color: Color){
+        if points.len() < 3 {
+            return;
+        }
+        let (width, height) = self.image.dimensions();
+        let min_y = points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min).max(0.0) as u32;
+        let max_y = points.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max)
+            .min(height as f32) as u32;
+        let mut crossings = Vec::new();
+        for y in min_y..max_y.min(height) {
+            let sample_y = y as f32 + 0.5;
+            crossings.clear();
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if (a.y <= sample_y && b.y > sample_y) || (b.y <= sample_y && a.y > sample_y) {
+                    let t = (sample_y - a.y) / (b.y - a.y);
+                    crossings.push(a.x + t * (b.x - a.x));
+                }
+            }
+            crossings.sort_by(|a, b| a.total_cmp(b));
+            for span in crossings.chunks(2) {
+                if let [start, end] = span {
+                    let start = (start - 0.5).ceil().max(0.0) as u32;
+                    let end = (end - 0.5).ceil().min(width as f32).max(0.0) as u32;
+                    for x in start..end {
+                        self.blend(x, y, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Strokes the closed outline through `points`.
+    pub fn stroke_polygon(&mut self, points: &[Point], width: f32, color: Color){
+        for (i, point) in points.iter().enumerate() {
+            self.stroke_line(*point, points[(i + 1) % points.len()], width, color);
+        }
+    }
+
+    pub fn stroke_line(&mut self, from: Point, to: Point, width: f32, color: Color){
+        let direction = to - from;
+        let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+        if length == 0.0 {
+            return;
+        }
+        let normal = Vector::new(-direction.y, direction.x) * (width / 2.0 / length);
+        self.fill_polygon(&[from + normal, to + normal, to - normal, from - normal], color);
+    }
+
+    /// Draws a line of text `size` pixels high, centered on `center`, in
+    /// the font the canvas draws text with.
+    pub fn fill_text(&mut self, text: &str, center: Point, size: f32, color: Color){
+        let font = font();
+        let font = font.as_scaled(PxScale::from(size));
+        let (glyphs, width) = layout(&font, text);
+        // Centers the block from the ascent down to the descent.
+        let origin = point(center.x - width / 2.0, center.y + (font.ascent() + font.descent()) / 2.0);
+        let (image_width, image_height) = self.image.dimensions();
+        for mut glyph in glyphs {
+            glyph.position += origin;
+            let outline = match font.outline_glyph(glyph) {
+                Some(outline) => outline,
+                None => continue,
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|x, y, coverage| {
+                let (x, y) = (bounds.min.x as i64 + x as i64, bounds.min.y as i64 + y as i64);
+                if (0..image_width as i64).contains(&x) && (0..image_height as i64).contains(&y) {
+                    self.blend(x as u32, y as u32, Color{ a: color.a * coverage.min(1.0), ..color });
+                }
+            });
+        }
+    }
+
+    /// Width in pixels of `text` drawn `size` pixels high by `fill_text`.
+    pub fn text_width(text: &str, size: f32) -> f32{
+        layout(&font().as_scaled(PxScale::from(size)), text).1
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: Color){
+        let pixel = self.image.get_pixel_mut(x, y);
+        let alpha = color.a;
+        let [r, g, b, a] = pixel.0;
+        let mix = |dst: u8, src: f32| (src * 255.0 * alpha + dst as f32 * (1.0 - alpha)).round() as u8;
+        *pixel = Rgba([
+            mix(r, color.r),
+            mix(g, color.g),
+            mix(b, color.b),
+            (alpha * 255.0 + a as f32 * (1.0 - alpha)).round() as u8,
+        ]);
+    }
+}
+
+/// The font iced falls back on, which the canvas draws text with.
+fn font() -> FontRef<'static>{
+    FontRef::try_from_slice(iced_graphics::font::FALLBACK).expect("the built-in font is valid")
+}
+
+/// Glyphs of `text` on one line from the origin, and the line's width.
+fn layout<F: Font>(font: &impl ScaleFont<F>, text: &str) -> (Vec<Glyph>, f32){
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += font.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(font.scale(), point(caret, 0.0)));
+        caret += font.h_advance(id);
+        previous = Some(id);
+    }
+    (glyphs, caret)
+}
+
+fn to_rgba(color: Color) -> Rgba<u8>{
+    Rgba([
+        (color.r * 255.0).round() as u8,
+        (color.g * 255.0).round() as u8,
+        (color.b * 255.0).round() as u8,
+        (color.a * 255.0).round() as u8,
+    ])
+}
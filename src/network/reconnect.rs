@@ -0,0 +1,93 @@
+use iced::{Point, Rectangle};
+use iced_graphics::canvas::{Cursor, Frame, Path, Stroke};
+
+use super::{EdgeError, Interaction, Network};
+
+/// One of the two ends of an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeEnd{
+    From,
+    To,
+}
+
+impl Network{
+    /// Distance in pixels within which a press grabs the end of a selected
+    /// edge.
+    const EDGE_END_HIT_DISTANCE: f32 = 8.0;
+    const EDGE_END_HANDLE_RADIUS: f32 = 5.0;
+
+    /// Connects one end of an edge to `node_id` instead, dropping the port
+    /// it was attached to. The edge must stay valid like a new one, see
+    /// `check_edge`; otherwise it is left as it was.
+    pub fn reconnect_edge(&mut self, edge_id: u32, end: EdgeEnd, node_id: u32) -> Result<(), EdgeError>{
+        let edge = self.edges.iter().find(|edge| edge.id == edge_id).ok_or(EdgeError::UnknownEdge(edge_id))?;
+        let (from, to) = match end {
+            EdgeEnd::From => (node_id, edge.to),
+            EdgeEnd::To => (edge.from, node_id),
+        };
+        if (from, to) == (edge.from, edge.to) {
+            return Ok(());
+        }
+        self.check_edge_except(from, to, Some(edge_id))?;
+        if let Some(edge) = self.edges.iter_mut().find(|edge| edge.id == edge_id) {
+            match end {
+                EdgeEnd::From => {
+                    edge.from = from;
+                    edge.from_port = None;
+                }
+                EdgeEnd::To => {
+                    edge.to = to;
+                    edge.to_port = None;
+                }
+            }
+        }
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// The end of a selected edge within reach of the world position
+    /// `world`, the nearest one if several are.
+    pub(super) fn edge_end_at(&self, world: Point) -> Option<(u32, EdgeEnd)>{
+        let reach = Self::EDGE_END_HIT_DISTANCE / self.scaling;
+        self.edges.iter()
+            .filter(|edge| edge.is_selected && self.is_layer_available(&edge.layer))
+            .filter_map(|edge| Some((edge.id, self.edge_route(edge)?)))
+            .flat_map(|(id, route)| [(id, EdgeEnd::From, route.from), (id, EdgeEnd::To, route.to)])
+            .map(|(id, end, point)| (id, end, point.distance(world)))
+            .filter(|(_, _, distance)| *distance <= reach)
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+            .map(|(id, end, _)| (id, end))
+    }
+
+    /// Draws grab handles on the ends of the selected edges and, while an
+    /// end is dragged, a line from the other end to the cursor, in screen
+    /// space.
+    pub(super) fn draw_edge_ends(&self, frame: &mut Frame, bounds: Rectangle, cursor: Cursor){
+        let size = bounds.size();
+        let stroke = Stroke{ color: self.theme.selection, width: 1.5, ..Stroke::default() };
+        let dragged = match self.interaction {
+            Interaction::ReconnectingEdge { edge_id, end } => Some((edge_id, end)),
+            _ => None,
+        };
+        for edge in self.edges.iter().filter(|edge| edge.is_selected) {
+            let route = match self.edge_route(edge) {
+                Some(route) => route,
+                None => continue,
+            };
+            for (end, point) in [(EdgeEnd::From, route.from), (EdgeEnd::To, route.to)] {
+                let handle = Path::circle(self.world_to_screen(point, size), Self::EDGE_END_HANDLE_RADIUS);
+                frame.fill(&handle, self.theme.label);
+                frame.stroke(&handle, stroke);
+                if dragged == Some((edge.id, end)) {
+                    let fixed = match end {
+                        EdgeEnd::From => route.to,
+                        EdgeEnd::To => route.from,
+                    };
+                    if let Some(position) = cursor.position_in(&bounds) {
+                        frame.stroke(&Path::line(self.world_to_screen(fixed, size), position), stroke);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use iced::{Color, Point, Rectangle, Size};
+use iced_graphics::canvas::{Cursor, Frame, Path, Stroke, Text};
+
+use super::Network;
+
+impl Network{
+    /// Thickness of the rulers, in pixels.
+    const RULER_SIZE: f32 = 20.0;
+    /// Least room between labelled ticks, in pixels.
+    const RULER_TICK_SPACING: f32 = 60.0;
+    const RULER_LABEL_SIZE: f32 = 11.0;
+    /// Minor ticks between two labelled ones.
+    const RULER_MINOR_TICKS: usize = 5;
+    const ORIGIN_RADIUS: f32 = 4.0;
+
+    /// Shows or hides the rulers along the top and left of the canvas.
+    pub fn set_rulers_visible(&mut self, visible: bool){
+        self.show_rulers = visible;
+    }
+
+    /// Shows or hides the world axes through the origin.
+    pub fn set_axes_visible(&mut self, visible: bool){
+        self.show_axes = visible;
+    }
+
+    /// Draws the world x and y axes across the view and marks the origin,
+    /// in screen space.
+    pub(super) fn draw_axes(&self, frame: &mut Frame, size: Size){
+        let origin = self.world_to_screen(Point::ORIGIN, size);
+        let color = self.theme.line;
+        let stroke = Stroke{ color: Color{ a: color.a * 0.5, ..color }, width: 1.0, ..Stroke::default() };
+        if (0.0..=size.height).contains(&origin.y) {
+            frame.stroke(&Path::line(Point::new(0.0, origin.y), Point::new(size.width, origin.y)), stroke);
+        }
+        if (0.0..=size.width).contains(&origin.x) {
+            frame.stroke(&Path::line(Point::new(origin.x, 0.0), Point::new(origin.x, size.height)), stroke);
+        }
+        frame.fill(&Path::circle(origin, Self::ORIGIN_RADIUS), color);
+    }
+
+    /// Draws rulers with world coordinates along the top and left edges,
+    /// marking where the cursor is, in screen space.
+    pub(super) fn draw_rulers(&self, frame: &mut Frame, bounds: Rectangle, cursor: Cursor){
+        let size = bounds.size();
+        let region = self.visible_region(size);
+        let step = Self::ruler_step(Self::RULER_TICK_SPACING / self.scaling);
+        let thickness = Self::RULER_SIZE;
+        let backdrop = self.theme.backdrop();
+        frame.fill(&Path::rectangle(Point::ORIGIN, Size::new(size.width, thickness)), backdrop);
+        frame.fill(&Path::rectangle(Point::ORIGIN, Size::new(thickness, size.height)), backdrop);
+        let stroke = Stroke{ color: self.theme.label, width: 1.0, ..Stroke::default() };
+
+        let minor = step / Self::RULER_MINOR_TICKS as f32;
+        let first = (region.x / minor).floor() as i64;
+        let last = ((region.x + region.width) / minor).ceil() as i64;
+        for i in first..=last {
+            let x = self.world_to_screen(Point::new(i as f32 * minor, 0.0), size).x;
+            if x < thickness {
+                continue;
+            }
+            let major = i % Self::RULER_MINOR_TICKS as i64 == 0;
+            let length = if major { thickness } else { thickness / 4.0 };
+            frame.stroke(&Path::line(Point::new(x, thickness - length), Point::new(x, thickness)), stroke);
+            if major {
+                frame.fill_text(Text{
+                    content: Self::ruler_label(i as f32 * minor),
+                    position: Point::new(x + 3.0, 2.0),
+                    color: self.theme.label,
+                    size: Self::RULER_LABEL_SIZE,
+                    ..Text::default()
+                });
+            }
+        }
+
+        let first = (region.y / minor).floor() as i64;
+        let last = ((region.y + region.height) / minor).ceil() as i64;
+        for i in first..=last {
+            let y = self.world_to_screen(Point::new(0.0, i as f32 * minor), size).y;
+            if y < thickness {
+                continue;
+            }
+            let major = i % Self::RULER_MINOR_TICKS as i64 == 0;
+            let length = if major { thickness } else { thickness / 4.0 };
+            frame.stroke(&Path::line(Point::new(thickness - length, y), Point::new(thickness, y)), stroke);
+            if major {
+                // Canvas text can't be rotated, so the labels run across.
+                frame.fill_text(Text{
+                    content: Self::ruler_label(i as f32 * minor),
+                    position: Point::new(2.0, y + 2.0),
+                    color: self.theme.label,
+                    size: Self::RULER_LABEL_SIZE,
+                    ..Text::default()
+                });
+            }
+        }
+
+        if let Some(position) = cursor.position_in(&bounds) {
+            let marker = Stroke{ color: self.theme.selection, width: 2.0, ..Stroke::default() };
+            frame.stroke(&Path::line(Point::new(position.x, 0.0), Point::new(position.x, thickness)), marker);
+            frame.stroke(&Path::line(Point::new(0.0, position.y), Point::new(thickness, position.y)), marker);
+        }
+        // The corner where the rulers meet.
+        frame.fill(&Path::rectangle(Point::ORIGIN, Size::new(thickness, thickness)), self.theme.background);
+    }
+
+    /// Smallest of 1, 2 and 5 times a power of ten that is at least `least`.
+    fn ruler_step(least: f32) -> f32{
+        let power = 10f32.powf(least.log10().floor());
+        [1.0, 2.0, 5.0, 10.0].into_iter()
+            .map(|factor| factor * power)
+            .find(|step| *step >= least)
+            .unwrap_or(10.0 * power)
+    }
+
+    /// Tick label, without decimals unless the ticks are that close.
+    fn ruler_label(value: f32) -> String{
+        if value.fract().abs() < 1e-3 {
+            format!("{}", value.round() as i64)
+        } else {
+            format!("{:.2}", value).trim_end_matches('0').to_string()
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use iced::{Color, Point, Size, Vector};
+use iced::keyboard::KeyCode;
+use iced_graphics::canvas::{event, Frame, Path, Text};
+
+use super::{Network, NetworkMessage};
+
+impl Network{
+    const SEARCH_BOX_SIZE: Size = Size::new(280.0, 32.0);
+    const SEARCH_BOX_MARGIN: f32 = 10.0;
+    /// Number of results listed under the search box.
+    const SEARCH_RESULTS_SHOWN: usize = 5;
+    const SEARCH_RESULT_HEIGHT: f32 = 24.0;
+
+    /// Ids of the nodes matching the search query, best match first; see
+    /// `fuzzy_search`.
+    pub fn search_matches(&self) -> Vec<u32>{
+        self.fuzzy_search(&self.search_query)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Scores every node label against `query`, ignoring case, and returns
+    /// the matching nodes with their scores, best first and by id on ties.
+    /// Scores are between 0 and 1: an exact label scores 1, then come
+    /// prefixes, substrings and subsequences of the label, then labels
+    /// containing the query with a few typos. Hidden nodes are skipped and
+    /// an empty query matches nothing.
+    pub fn fuzzy_search(&self, query: &str) -> Vec<(u32, f32)>{
+        let query: Vec<char> = query.to_lowercase().chars().collect();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<(u32, f32)> = self.nodes.iter()
+            .filter(|node| !self.is_hidden(node))
+            .filter_map(|node| {
+                let label: Vec<char> = node.label.to_lowercase().chars().collect();
+                Some((node.id, match_score(&query, &label)?))
+            })
+            .collect();
+        matches.sort_by(|(a_id, a), (b_id, b)| b.total_cmp(a).then(a_id.cmp(b_id)));
+        matches
+    }
+
+    pub(super) fn start_search(&mut self){
+        self.searching = true;
+        self.search_query.clear();
+        self.search_index = 0;
+    }
+
+    pub(super) fn handle_search_key(&mut self, key_code: KeyCode) -> (event::Status, Option<NetworkMessage>){
+        match key_code {
+            KeyCode::Escape => self.searching = false,
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                self.search_index += 1;
+                self.select_search_match();
+            }
+            KeyCode::Backspace => {
+                self.search_query.pop();
+                self.search_index = 0;
+                self.select_search_match();
+            }
+            _ => {}
+        }
+        (event::Status::Captured, None)
+    }
+
+    pub(super) fn handle_search_character(&mut self, c: char){
+        if c.is_control() {
+            return;
+        }
+        self.search_query.push(c);
+        self.search_index = 0;
+        self.select_search_match();
+    }
+
+    /// Selects the current match, wrapping around, and centers the view on it.
+    fn select_search_match(&mut self){
+        let matches = self.search_matches();
+        if matches.is_empty() {
+            return;
+        }
+        self.search_index %= matches.len();
+        let id = matches[self.search_index];
+        self.unselect_all_nodes();
+        self.unselect_all_edges();
+        self.unselect_all_groups();
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.set_selected(true);
+        }
+        self.focus_node(id, None);
+        self.mark_dirty();
+    }
+
+    /// Draws the search box with the query and match position in the
+    /// top-left corner, in screen space, and the best matches under it.
+    pub(super) fn draw_search_overlay(&self, frame: &mut Frame){
+        let position = Point::new(Self::SEARCH_BOX_MARGIN, Self::SEARCH_BOX_MARGIN);
+        let background = Path::rectangle(position, Self::SEARCH_BOX_SIZE);
+        frame.fill(&background, self.theme.backdrop());
+        let matches = self.search_matches();
+        let count = matches.len();
+        let status = if count == 0 {
+            String::from("no matches")
+        } else {
+            format!("{}/{}", self.search_index % count + 1, count)
+        };
+        frame.fill_text(Text{
+            content: format!("Find: {}_   ({})", self.search_query, status),
+            position: position + Vector::new(8.0, 8.0),
+            color: self.theme.label,
+            ..Text::default()
+        });
+
+        let shown = count.min(Self::SEARCH_RESULTS_SHOWN);
+        if shown == 0 {
+            return;
+        }
+        let list = position + Vector::new(0.0, Self::SEARCH_BOX_SIZE.height);
+        let size = Size::new(Self::SEARCH_BOX_SIZE.width, shown as f32 * Self::SEARCH_RESULT_HEIGHT);
+        frame.fill(&Path::rectangle(list, size), self.theme.backdrop());
+        for (i, id) in matches.iter().take(shown).enumerate() {
+            let row = list + Vector::new(0.0, i as f32 * Self::SEARCH_RESULT_HEIGHT);
+            let current = i == self.search_index % count;
+            if current {
+                let highlight = Path::rectangle(row, Size::new(Self::SEARCH_BOX_SIZE.width, Self::SEARCH_RESULT_HEIGHT));
+                frame.fill(&highlight, Color{ a: 0.3, ..self.theme.selection });
+            }
+            let label = self.node(*id).map_or("", |node| node.label.as_str());
+            frame.fill_text(Text{
+                content: label.to_string(),
+                position: row + Vector::new(16.0, 4.0),
+                color: self.theme.label,
+                ..Text::default()
+            });
+        }
+    }
+}
+
+/// Score of `label` against a non-empty `query`, both lowercase, or
+/// `None` if they are too far apart; see `Network::fuzzy_search`.
+fn match_score(query: &[char], label: &[char]) -> Option<f32>{
+    if label == query {
+        return Some(1.0);
+    }
+    if label.starts_with(query) {
+        return Some(0.9);
+    }
+    if label.windows(query.len()).any(|window| window == query) {
+        return Some(0.8);
+    }
+    if let Some(span) = subsequence_span(query, label) {
+        // Tighter subsequences score higher.
+        return Some(0.4 + 0.3 * query.len() as f32 / span as f32);
+    }
+    let typos = substring_distance(query, label);
+    let allowed = query.len() / 3;
+    if typos <= allowed {
+        return Some(0.4 * (1.0 - typos as f32 / query.len() as f32));
+    }
+    None
+}
+
+/// Length of the part of `label` from the first to the last character of
+/// `query` when its characters appear in order, matched greedily.
+fn subsequence_span(query: &[char], label: &[char]) -> Option<usize>{
+    let mut rest = query.iter().peekable();
+    let mut first = None;
+    for (i, c) in label.iter().enumerate() {
+        if rest.peek() == Some(&c) {
+            rest.next();
+            first.get_or_insert(i);
+            if rest.peek().is_none() {
+                return Some(i + 1 - first?);
+            }
+        }
+    }
+    None
+}
+
+/// Fewest insertions, deletions and substitutions turning `query` into
+/// some substring of `label`.
+fn substring_distance(query: &[char], label: &[char]) -> usize{
+    // Row for matching no query characters: every start in `label` is free.
+    let mut previous = vec![0; label.len() + 1];
+    for (i, q) in query.iter().enumerate() {
+        let mut current = vec![i + 1; label.len() + 1];
+        for (j, c) in label.iter().enumerate() {
+            let substitution = previous[j] + (q != c) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous.into_iter().min().unwrap_or(query.len())
+}
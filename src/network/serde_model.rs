@@ -0,0 +1,452 @@
+//! Serializable mirror of the network, shared by every format that reads or
+//! writes graphs as JSON. iced's geometry types don't implement serde, so
+//! positions, sizes and colors are spelled out as plain numbers.
+
+use std::collections::{BTreeMap, HashSet};
+
+use iced::{Color, Point, Rectangle, Vector};
+use serde::{Deserialize, Serialize};
+
+use super::{Annotation, Edge, EdgeStyle, Group, Icon, Layer, LineStyle, LoadError, Network, Node, NodeShape, Port, TextAnnotation};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerNetwork{
+    pub nodes: Vec<SerNode>,
+    #[serde(default)]
+    pub edges: Vec<SerEdge>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<SerGroup>,
+    #[serde(default)]
+    pub edge_style: EdgeStyle,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<SerAnnotation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub texts: Vec<SerText>,
+    /// Bottom to top. Missing layers, including the default one, are added
+    /// on load.
+    #[serde(default, skip_serializing_if = "is_default_layers")]
+    pub layers: Vec<Layer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerNode{
+    pub id: u32,
+    #[serde(default)]
+    pub label: String,
+    /// Top-left corner, in world coordinates.
+    pub x: f32,
+    pub y: f32,
+    #[serde(default = "default_node_size")]
+    pub width: f32,
+    #[serde(default = "default_node_size")]
+    pub height: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<SerColor>,
+    #[serde(default)]
+    pub shape: NodeShape,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Icon>,
+    /// Missing ports default to one in the middle of each side.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ports: Option<Vec<SerPort>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default = "default_layer", skip_serializing_if = "is_default_layer")]
+    pub layer: String,
+    /// Pinned nodes have `x`, `y`, `width` and `height` in screen pixels.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Clockwise, in radians.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rotation: f32,
+    /// Opacity, from 0 to 1.
+    #[serde(default = "opaque", skip_serializing_if = "is_opaque")]
+    pub alpha: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerEdge{
+    /// Documents written by hand may leave edge ids out; they are handed
+    /// out on load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    pub from: u32,
+    pub to: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_port: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_port: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f32>,
+    #[serde(default, skip_serializing_if = "is_solid")]
+    pub line_style: LineStyle,
+    #[serde(default = "default_layer", skip_serializing_if = "is_default_layer")]
+    pub layer: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerGroup{
+    pub id: u32,
+    pub label: String,
+    pub member_ids: Vec<u32>,
+    #[serde(default)]
+    pub collapsed: bool,
+}
+
+/// Freehand stroke, as `[x, y]` world positions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerAnnotation{
+    pub points: Vec<[f32; 2]>,
+}
+
+/// Text annotation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerText{
+    pub id: u32,
+    /// Top-left corner, in world coordinates.
+    pub x: f32,
+    pub y: f32,
+    pub content: String,
+    #[serde(default = "default_text_size")]
+    pub size: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<SerColor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerPort{
+    pub name: String,
+    /// Position relative to the node's top-left corner.
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SerColor{
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    #[serde(default = "opaque")]
+    pub a: f32,
+}
+
+fn default_node_size() -> f32{
+    Network::DEFAULT_NODE_SIZE
+}
+
+fn default_text_size() -> f32{
+    TextAnnotation::DEFAULT_SIZE
+}
+
+fn default_layer() -> String{
+    String::from(Layer::DEFAULT)
+}
+
+fn is_zero(value: &f32) -> bool{
+    *value == 0.0
+}
+
+fn is_default_layer(layer: &str) -> bool{
+    layer == Layer::DEFAULT
+}
+
+fn is_default_layers(layers: &[Layer]) -> bool{
+    layers.len() == 1 && layers[0] == Layer::new(Layer::DEFAULT)
+}
+
+fn is_solid(style: &LineStyle) -> bool{
+    *style == LineStyle::Solid
+}
+
+fn opaque() -> f32{
+    1.0
+}
+
+fn is_opaque(alpha: &f32) -> bool{
+    *alpha == 1.0
+}
+
+impl From<Color> for SerColor{
+    fn from(color: Color) -> Self {
+        SerColor{ r: color.r, g: color.g, b: color.b, a: color.a }
+    }
+}
+
+impl From<SerColor> for Color{
+    fn from(color: SerColor) -> Self {
+        Color::from_rgba(color.r, color.g, color.b, color.a)
+    }
+}
+
+impl From<&Port> for SerPort{
+    fn from(port: &Port) -> Self {
+        SerPort{ name: port.name.clone(), x: port.offset.x, y: port.offset.y }
+    }
+}
+
+impl From<SerPort> for Port{
+    fn from(port: SerPort) -> Self {
+        Port::new(port.name, Vector::new(port.x, port.y))
+    }
+}
+
+impl From<&Node> for SerNode{
+    fn from(node: &Node) -> Self {
+        SerNode{
+            id: node.id,
+            label: node.label.clone(),
+            x: node.bounds.x,
+            y: node.bounds.y,
+            width: node.bounds.width,
+            height: node.bounds.height,
+            color: node.color.map(SerColor::from),
+            shape: node.shape,
+            icon: node.icon,
+            ports: Some(node.ports.iter().map(SerPort::from).collect()),
+            locked: node.locked,
+            metadata: node.metadata.iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            tags: node.tags.clone(),
+            layer: node.layer.clone(),
+            pinned: node.pinned,
+            rotation: node.rotation,
+            alpha: node.alpha,
+        }
+    }
+}
+
+impl From<SerNode> for Node{
+    fn from(ser: SerNode) -> Self {
+        let mut node = Node::new(ser.id, Rectangle{
+            x: ser.x,
+            y: ser.y,
+            width: ser.width,
+            height: ser.height,
+        });
+        node.label = ser.label;
+        node.color = ser.color.map(Color::from);
+        node.shape = ser.shape;
+        node.icon = ser.icon;
+        if let Some(ports) = ser.ports {
+            node.ports = ports.into_iter().map(Port::from).collect();
+        }
+        node.locked = ser.locked;
+        node.metadata = ser.metadata.into_iter().collect();
+        node.tags = ser.tags;
+        node.layer = ser.layer;
+        node.pinned = ser.pinned;
+        node.rotation = ser.rotation;
+        node.alpha = ser.alpha.clamp(0.0, 1.0);
+        node
+    }
+}
+
+impl From<&Edge> for SerEdge{
+    fn from(edge: &Edge) -> Self {
+        SerEdge{
+            id: Some(edge.id),
+            from: edge.from,
+            to: edge.to,
+            from_port: edge.from_port.clone(),
+            to_port: edge.to_port.clone(),
+            label: edge.label.clone(),
+            weight: edge.weight,
+            line_style: edge.line_dash,
+            layer: edge.layer.clone(),
+        }
+    }
+}
+
+impl From<&Group> for SerGroup{
+    fn from(group: &Group) -> Self {
+        SerGroup{
+            id: group.id,
+            label: group.label.clone(),
+            member_ids: group.member_ids.clone(),
+            collapsed: group.collapsed,
+        }
+    }
+}
+
+impl From<&Annotation> for SerAnnotation{
+    fn from(annotation: &Annotation) -> Self {
+        SerAnnotation{ points: annotation.points.iter().map(|p| [p.x, p.y]).collect() }
+    }
+}
+
+impl From<SerAnnotation> for Annotation{
+    fn from(ser: SerAnnotation) -> Self {
+        Annotation{ points: ser.points.into_iter().map(|[x, y]| Point::new(x, y)).collect() }
+    }
+}
+
+impl From<&TextAnnotation> for SerText{
+    fn from(text: &TextAnnotation) -> Self {
+        SerText{
+            id: text.id,
+            x: text.position.x,
+            y: text.position.y,
+            content: text.content.clone(),
+            size: text.size,
+            color: text.color.map(SerColor::from),
+        }
+    }
+}
+
+impl From<SerText> for TextAnnotation{
+    fn from(ser: SerText) -> Self {
+        TextAnnotation{
+            id: ser.id,
+            position: Point::new(ser.x, ser.y),
+            content: ser.content,
+            size: ser.size,
+            color: ser.color.map(Color::from),
+        }
+    }
+}
+
+impl From<&Network> for SerNetwork{
+    fn from(network: &Network) -> Self {
+        SerNetwork{
+            nodes: network.nodes.iter().map(SerNode::from).collect(),
+            edges: network.edges.iter().map(SerEdge::from).collect(),
+            groups: network.groups.iter().map(SerGroup::from).collect(),
+            edge_style: network.edge_style,
+            annotations: network.annotations.iter().map(SerAnnotation::from).collect(),
+            texts: network.texts.iter().map(SerText::from).collect(),
+            layers: network.layers.clone(),
+        }
+    }
+}
+
+impl TryFrom<SerNetwork> for Network{
+    type Error = LoadError;
+
+    /// Rebuilds a network, keeping the ids of the document. Fails on
+    /// duplicate ids and on edges or groups that refer to unknown nodes.
+    fn try_from(ser: SerNetwork) -> Result<Self, Self::Error> {
+        let mut network = Network::empty();
+        network.edge_style = ser.edge_style;
+        let mut ids = HashSet::new();
+        let mut claim = |id: u32| if ids.insert(id) {
+            Ok(id)
+        } else {
+            Err(LoadError::Parse(format!("duplicate id {}", id)))
+        };
+
+        for node in &ser.nodes {
+            claim(node.id)?;
+        }
+        let others = ser.groups.iter().map(|group| group.id).chain(ser.texts.iter().map(|text| text.id));
+        for id in ser.edges.iter().filter_map(|edge| edge.id).chain(others) {
+            claim(id)?;
+        }
+        network.next_id = match ids.iter().max() {
+            // Leaves room for the counter to move past every id.
+            Some(&max) => max.checked_add(1)
+                .ok_or_else(|| LoadError::Parse(format!("id {} is out of range", max)))?,
+            None => 0,
+        };
+        let known: HashSet<u32> = ser.nodes.iter().map(|node| node.id).collect();
+        let check = |id: u32| if known.contains(&id) {
+            Ok(id)
+        } else {
+            Err(LoadError::Parse(format!("reference to unknown node {}", id)))
+        };
+
+        network.nodes = ser.nodes.into_iter().map(Node::from).collect();
+        for ser in ser.edges {
+            let id = match ser.id {
+                Some(id) => id,
+                None => network.allocate_id(),
+            };
+            let mut edge = Edge::new(id, check(ser.from)?, check(ser.to)?);
+            edge.from_port = ser.from_port;
+            edge.to_port = ser.to_port;
+            edge.label = ser.label;
+            edge.weight = ser.weight;
+            edge.line_dash = ser.line_style;
+            edge.layer = ser.layer;
+            network.edges.push(edge);
+        }
+        for ser in ser.groups {
+            for id in &ser.member_ids {
+                check(*id)?;
+            }
+            network.groups.push(Group{
+                id: ser.id,
+                member_ids: ser.member_ids,
+                collapsed: ser.collapsed,
+                bounds: Rectangle::default(),
+                label: ser.label,
+                is_selected: false,
+            });
+        }
+        network.annotations = ser.annotations.into_iter().map(Annotation::from).collect();
+        network.texts = ser.texts.into_iter().map(TextAnnotation::from).collect();
+        network.layers = ser.layers;
+        network.ensure_layer(Layer::DEFAULT);
+        let used: Vec<String> = network.nodes.iter().map(|node| node.layer.clone())
+            .chain(network.edges.iter().map(|edge| edge.layer.clone()))
+            .collect();
+        for layer in used {
+            network.ensure_layer(&layer);
+        }
+        network.fit_groups();
+        network.fit_edge_widths();
+        network.fit_parallel_edges();
+        Ok(network)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::SerNetwork;
+    use crate::network::{LoadError, Network};
+
+    const DOCUMENT: &str = r#"{
+        "nodes": [
+            {"id": 1, "label": "a", "x": 0, "y": 0, "metadata": {"owner": "ops", "tier": "1"}},
+            {"id": 2, "label": "b", "x": 200, "y": 50, "width": 80, "height": 40, "color": {"r": 1.0, "g": 0.5, "b": 0.0, "a": 1.0}},
+            {"id": 3, "label": "c", "x": -100, "y": 120, "tags": ["db"]}
+        ],
+        "edges": [
+            {"id": 4, "from": 1, "to": 2, "label": "calls"},
+            {"id": 5, "from": 2, "to": 3}
+        ],
+        "groups": [
+            {"id": 6, "member_ids": [2, 3], "label": "backend"}
+        ]
+    }"#;
+
+    #[test]
+    fn save_and_load_round_trip(){
+        let original = Network::import_json(DOCUMENT).expect("the document is valid");
+        let loaded = Network::import_json(&original.export_json()).expect("a saved network loads");
+        let (before, after) = (SerNetwork::from(&original), SerNetwork::from(&loaded));
+        assert_eq!(after.nodes, before.nodes);
+        assert_eq!(after.edges, before.edges);
+        assert_eq!(after.groups, before.groups);
+        assert_eq!(after, before);
+
+        let document: SerNetwork = serde_json::from_str(DOCUMENT).unwrap();
+        assert_eq!(after.nodes.iter().map(|node| &node.metadata).collect::<Vec<_>>(),
+            document.nodes.iter().map(|node| &node.metadata).collect::<Vec<_>>());
+        assert_eq!(after.edges.iter().map(|edge| (edge.id, edge.from, edge.to)).collect::<Vec<_>>(),
+            [(Some(4), 1, 2), (Some(5), 2, 3)]);
+        assert_eq!(after.groups[0].member_ids, [2, 3]);
+    }
+
+    #[test]
+    fn largest_id_is_refused(){
+        let document = format!(r#"{{"nodes": [{{"id": {}, "x": 0, "y": 0}}]}}"#, u32::MAX);
+        assert!(matches!(Network::import_json(&document), Err(LoadError::Parse(_))));
+    }
+}
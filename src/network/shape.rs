@@ -0,0 +1,116 @@
+use iced::{Point, Rectangle, Vector};
+use iced_graphics::canvas::Path;
+use iced_graphics::canvas::path::arc;
+use serde::{Deserialize, Serialize};
+
+/// Outline a node is drawn with, fitted to its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeShape{
+    #[default]
+    Rectangle,
+    Ellipse,
+    Diamond,
+}
+
+impl NodeShape{
+    const ELLIPSE_SEGMENTS: usize = 48;
+
+    /// The shape that follows this one when cycling through shapes.
+    pub fn next(self) -> Self{
+        match self {
+            NodeShape::Rectangle => NodeShape::Ellipse,
+            NodeShape::Ellipse => NodeShape::Diamond,
+            NodeShape::Diamond => NodeShape::Rectangle,
+        }
+    }
+
+    pub fn path(&self, bounds: Rectangle) -> Path{
+        match self {
+            NodeShape::Rectangle => Path::rectangle(bounds.position(), bounds.size()),
+            NodeShape::Ellipse => Path::new(|builder| {
+                builder.ellipse(arc::Elliptical{
+                    center: bounds.center(),
+                    radii: Vector::new(bounds.width / 2.0, bounds.height / 2.0),
+                    rotation: 0.0,
+                    start_angle: 0.0,
+                    end_angle: 2.0 * std::f32::consts::PI,
+                });
+            }),
+            NodeShape::Diamond => {
+                let corners = self.outline(bounds);
+                Path::new(|builder| {
+                    builder.move_to(corners[0]);
+                    for corner in &corners[1..] {
+                        builder.line_to(*corner);
+                    }
+                    builder.close();
+                })
+            }
+        }
+    }
+
+    /// Corners of the shape's outline, with the ellipse approximated by line
+    /// segments.
+    pub fn outline(&self, bounds: Rectangle) -> Vec<Point>{
+        let center = bounds.center();
+        match self {
+            NodeShape::Rectangle => vec![
+                bounds.position(),
+                Point::new(bounds.x + bounds.width, bounds.y),
+                Point::new(bounds.x + bounds.width, bounds.y + bounds.height),
+                Point::new(bounds.x, bounds.y + bounds.height),
+            ],
+            NodeShape::Ellipse => (0..Self::ELLIPSE_SEGMENTS)
+                .map(|i| {
+                    let angle = 2.0 * std::f32::consts::PI * i as f32
+                        / Self::ELLIPSE_SEGMENTS as f32;
+                    Point::new(
+                        center.x + bounds.width / 2.0 * angle.cos(),
+                        center.y + bounds.height / 2.0 * angle.sin(),
+                    )
+                })
+                .collect(),
+            NodeShape::Diamond => vec![
+                Point::new(center.x, bounds.y),
+                Point::new(bounds.x + bounds.width, center.y),
+                Point::new(center.x, bounds.y + bounds.height),
+                Point::new(bounds.x, center.y),
+            ],
+        }
+    }
+
+    pub fn contains(&self, bounds: Rectangle, point: Point) -> bool{
+        if !bounds.contains(point) {
+            return false;
+        }
+        let center = bounds.center();
+        let dx = (point.x - center.x) / (bounds.width / 2.0);
+        let dy = (point.y - center.y) / (bounds.height / 2.0);
+        match self {
+            NodeShape::Rectangle => true,
+            NodeShape::Ellipse => dx * dx + dy * dy <= 1.0,
+            NodeShape::Diamond => dx.abs() + dy.abs() <= 1.0,
+        }
+    }
+
+    /// Point where the segment from the center of `bounds` towards `outside`
+    /// crosses the outline of the shape.
+    pub fn boundary_point(&self, bounds: Rectangle, outside: Point) -> Point{
+        let center = bounds.center();
+        let direction = outside - center;
+        if direction.x == 0.0 && direction.y == 0.0 {
+            return center;
+        }
+        // Direction expressed in units of the half extents, so the outline
+        // becomes the unit square, circle or diamond.
+        let nx = direction.x.abs() / (bounds.width / 2.0);
+        let ny = direction.y.abs() / (bounds.height / 2.0);
+        let t = match self {
+            NodeShape::Rectangle => 1.0 / nx.max(ny),
+            NodeShape::Ellipse => 1.0 / (nx * nx + ny * ny).sqrt(),
+            NodeShape::Diamond => 1.0 / (nx + ny),
+        };
+        center + direction * t.min(1.0)
+    }
+}
@@ -0,0 +1,120 @@
+use iced::{Point, Rectangle, Size, Vector};
+use iced_graphics::canvas::{Frame, Path, Stroke};
+
+use super::{Axis, Network};
+
+/// Line drawn while dragging to show what the dragged node lines up with.
+pub(super) struct Guide{
+    /// `Vertical` guides run along a constant x, `Horizontal` ones along a
+    /// constant y.
+    axis: Axis,
+    /// The constant coordinate, in world space.
+    at: f32,
+    /// Extent along the guide, in world space.
+    from: f32,
+    to: f32,
+}
+
+impl Network{
+    /// Distance in pixels within which a dragged node snaps to another
+    /// node's edges or center.
+    const SNAP_DISTANCE: f32 = 6.0;
+
+    /// Where a node dragged to `to` should go to line up with the visible
+    /// nodes around it, and the guides showing the alignments. The other
+    /// selected nodes move along and are ignored. Holding Alt turns
+    /// snapping off.
+    pub(super) fn snap_drag(&self, node_id: u32, to: Vector, size: Size) -> (Vector, Vec<Guide>){
+        let node = match self.node(node_id) {
+            Some(node) if !self.modifiers.alt() => node,
+            _ => return (to, Vec::new()),
+        };
+        let region = self.visible_region(size);
+        let others: Vec<Rectangle> = self.nodes.iter()
+            .filter(|other| !other.is_selected && !other.pinned && !self.is_hidden(other))
+            .map(|other| other.bounds)
+            .filter(|bounds| region.intersects(*bounds, 0.0))
+            .collect();
+        let reach = Self::SNAP_DISTANCE / self.scaling;
+        let moved = Rectangle{ x: to.x, y: to.y, ..node.bounds };
+
+        let dx = nearest_offset(&others, moved, reach, x_lines);
+        let dy = nearest_offset(&others, moved, reach, y_lines);
+        let snapped = Rectangle{ x: moved.x + dx.unwrap_or(0.0), y: moved.y + dy.unwrap_or(0.0), ..moved };
+        let mut guides = Vec::new();
+        if dx.is_some() {
+            guides.extend(guides_along(&others, snapped, Axis::Vertical));
+        }
+        if dy.is_some() {
+            guides.extend(guides_along(&others, snapped, Axis::Horizontal));
+        }
+        (Vector::new(snapped.x, snapped.y), guides)
+    }
+
+    /// Draws the alignment guides of the drag in progress, in screen space.
+    pub(super) fn draw_guides(&self, frame: &mut Frame, size: Size){
+        let stroke = Stroke{ color: self.theme.selection, width: 1.0, ..Stroke::default() };
+        for guide in &self.guides {
+            let (from, to) = match guide.axis {
+                Axis::Vertical => (Point::new(guide.at, guide.from), Point::new(guide.at, guide.to)),
+                Axis::Horizontal => (Point::new(guide.from, guide.at), Point::new(guide.to, guide.at)),
+            };
+            frame.stroke(&Path::line(self.world_to_screen(from, size), self.world_to_screen(to, size)), stroke);
+        }
+    }
+}
+
+/// Left side, center and right side.
+fn x_lines(bounds: Rectangle) -> [f32; 3]{
+    [bounds.x, bounds.center_x(), bounds.x + bounds.width]
+}
+
+/// Top side, center and bottom side.
+fn y_lines(bounds: Rectangle) -> [f32; 3]{
+    [bounds.y, bounds.center_y(), bounds.y + bounds.height]
+}
+
+/// Smallest shift, no longer than `reach`, that puts one of the lines of
+/// `moved` on a line of one of `others`.
+fn nearest_offset(others: &[Rectangle], moved: Rectangle, reach: f32, lines: fn(Rectangle) -> [f32; 3]) -> Option<f32>{
+    let mut best: Option<f32> = None;
+    for other in others {
+        for a in lines(moved) {
+            for b in lines(*other) {
+                let offset = b - a;
+                if offset.abs() <= reach && best.is_none_or(|best| offset.abs() < best.abs()) {
+                    best = Some(offset);
+                }
+            }
+        }
+    }
+    best
+}
+
+/// One guide for every line of `moved` that lines up with `others`,
+/// spanning the nodes on it.
+fn guides_along(others: &[Rectangle], moved: Rectangle, axis: Axis) -> Vec<Guide>{
+    let lines: fn(Rectangle) -> [f32; 3] = match axis {
+        Axis::Vertical => x_lines,
+        Axis::Horizontal => y_lines,
+    };
+    let extent = |bounds: Rectangle| match axis {
+        Axis::Vertical => (bounds.y, bounds.y + bounds.height),
+        Axis::Horizontal => (bounds.x, bounds.x + bounds.width),
+    };
+    let mut guides = Vec::new();
+    for at in lines(moved) {
+        let (mut from, mut to) = extent(moved);
+        let mut aligned = false;
+        for other in others.iter().filter(|other| lines(**other).iter().any(|b| (b - at).abs() < 0.01)) {
+            let (start, end) = extent(*other);
+            from = from.min(start);
+            to = to.max(end);
+            aligned = true;
+        }
+        if aligned {
+            guides.push(Guide{ axis, at, from, to });
+        }
+    }
+    guides
+}
@@ -0,0 +1,72 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use iced::{Point, Size, Vector};
+use iced_graphics::canvas::{Frame, Path, Text};
+
+use super::Network;
+
+/// Frame timing measured by `draw`, which only gets `&self`.
+#[derive(Default)]
+pub(super) struct FrameStats{
+    last_frame: Cell<Option<Instant>>,
+    /// Frames per second, smoothed over the last few frames.
+    fps: Cell<f32>,
+    draw_time: Cell<Duration>,
+}
+
+impl FrameStats{
+    /// Weight of the newest frame in the smoothed rate.
+    const SMOOTHING: f32 = 0.1;
+
+    /// Records that a frame started drawing at `now`.
+    pub(super) fn frame_started(&self, now: Instant){
+        if let Some(last) = self.last_frame.get() {
+            let seconds = now.duration_since(last).as_secs_f32();
+            if seconds > 0.0 {
+                let fps = self.fps.get();
+                let instant_fps = 1.0 / seconds;
+                self.fps.set(if fps == 0.0 {
+                    instant_fps
+                } else {
+                    fps + (instant_fps - fps) * Self::SMOOTHING
+                });
+            }
+        }
+        self.last_frame.set(Some(now));
+    }
+
+    pub(super) fn frame_finished(&self, started: Instant){
+        self.draw_time.set(started.elapsed());
+    }
+}
+
+impl Network{
+    const STATS_BOX_SIZE: Size = Size::new(170.0, 26.0);
+    const STATS_BOX_MARGIN: f32 = 10.0;
+
+    /// Shows or hides the frame rate and draw time overlay.
+    pub fn set_frame_stats_visible(&mut self, visible: bool){
+        self.show_frame_stats = visible;
+    }
+
+    /// Draws the frame rate and the previous frame's draw time in the
+    /// top-right corner, in screen space.
+    pub(super) fn draw_frame_stats(&self, frame: &mut Frame){
+        let position = Point::new(
+            frame.width() - Self::STATS_BOX_SIZE.width - Self::STATS_BOX_MARGIN,
+            Self::STATS_BOX_MARGIN,
+        );
+        frame.fill(&Path::rectangle(position, Self::STATS_BOX_SIZE), self.theme.backdrop());
+        frame.fill_text(Text{
+            content: format!(
+                "{:.0} fps  {:.2} ms",
+                self.frame_stats.fps.get(),
+                self.frame_stats.draw_time.get().as_secs_f64() * 1000.0,
+            ),
+            position: position + Vector::new(8.0, 4.0),
+            color: self.theme.label,
+            ..Text::default()
+        });
+    }
+}
@@ -0,0 +1,55 @@
+use super::{Edge, Network, Node};
+
+impl Network{
+    /// Every tag used by some node, sorted and listed once.
+    pub fn tags(&self) -> Vec<String>{
+        let mut tags: Vec<String> = self.nodes.iter()
+            .flat_map(|node| node.tags.iter().cloned())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Replaces the tags of a node. Blank tags and repeats are dropped.
+    pub fn set_node_tags(&mut self, id: u32, tags: Vec<String>){
+        if let Some(node) = self.nodes.iter_mut().find(|x| x.id == id) {
+            node.tags.clear();
+            for tag in tags {
+                let tag = tag.trim();
+                if !tag.is_empty() && !node.tags.iter().any(|x| x == tag) {
+                    node.tags.push(tag.to_string());
+                }
+            }
+            self.mark_dirty();
+        }
+    }
+
+    pub fn tag_filter(&self) -> Option<&str>{
+        self.active_tag_filter.as_deref()
+    }
+
+    /// Dims every node without the given tag, and the edges touching them,
+    /// or shows everything normally again with `None`.
+    pub fn set_tag_filter(&mut self, tag: Option<String>){
+        if self.active_tag_filter != tag {
+            self.active_tag_filter = tag;
+            self.request_redraw();
+        }
+    }
+
+    /// Whether the tag filter dims the node.
+    pub(super) fn is_filtered_out(&self, node: &Node) -> bool{
+        match &self.active_tag_filter {
+            Some(tag) => !node.tags.contains(tag),
+            None => false,
+        }
+    }
+
+    /// Whether the tag filter dims the edge: it does if it dims either end.
+    pub(super) fn is_edge_filtered_out(&self, edge: &Edge) -> bool{
+        self.active_tag_filter.is_some()
+            && [edge.from, edge.to].iter()
+                .any(|id| self.node(*id).is_some_and(|node| self.is_filtered_out(node)))
+    }
+}
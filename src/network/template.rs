@@ -0,0 +1,113 @@
+use iced::{Color, Point, Rectangle, Size};
+use iced_graphics::canvas::{Cursor, Frame, Stroke};
+
+use super::{Network, Node, NodeShape};
+
+/// Kind of node offered by the palette: what a node created from it looks
+/// like.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeTemplate{
+    pub name: String,
+    pub shape: NodeShape,
+    /// Fill, or `None` to use the theme's node color.
+    pub color: Option<Color>,
+    pub size: Size,
+}
+
+impl NodeTemplate{
+    /// Templates offered when the embedder doesn't provide its own, sized
+    /// around `size`; see `Network::template_presets`.
+    pub fn presets(size: Size) -> Vec<NodeTemplate>{
+        vec![
+            NodeTemplate{ name: String::from("Box"), shape: NodeShape::Rectangle, color: None, size },
+            NodeTemplate{
+                name: String::from("Round"),
+                shape: NodeShape::Ellipse,
+                color: Some(Color::from_rgb(0.20, 0.55, 0.85)),
+                size,
+            },
+            NodeTemplate{
+                name: String::from("Decision"),
+                shape: NodeShape::Diamond,
+                color: Some(Color::from_rgb(1.00, 0.50, 0.00)),
+                size: Size::new(size.width * 1.4, size.height),
+            },
+            NodeTemplate{
+                name: String::from("Small"),
+                shape: NodeShape::Rectangle,
+                color: Some(Color::from_rgb(0.30, 0.69, 0.29)),
+                size: Size::new(size.width * 0.6, size.height * 0.6),
+            },
+        ]
+    }
+
+    /// A node made from the template, centered on the world position
+    /// `center`. Its id is handed out by `Network::add_node`.
+    pub fn node_at(&self, center: Point) -> Node{
+        let mut node = Node::with_bounds(Rectangle{
+            x: center.x - self.size.width / 2.0,
+            y: center.y - self.size.height / 2.0,
+            width: self.size.width,
+            height: self.size.height,
+        });
+        node.shape = self.shape;
+        node.color = self.color;
+        node
+    }
+}
+
+impl Network{
+    /// The preset templates, sized around the default node size.
+    pub fn template_presets(&self) -> Vec<NodeTemplate>{
+        NodeTemplate::presets(self.default_node_size)
+    }
+
+    /// Starts dragging a template from the palette. Releasing the button
+    /// over the canvas creates a node from it there.
+    pub fn start_template_drag(&mut self, template: NodeTemplate){
+        self.template_drag = Some(template);
+    }
+
+    /// Stops dragging a template without creating a node, e.g. when the
+    /// button is released outside the canvas.
+    pub fn end_template_drag(&mut self){
+        self.template_drag = None;
+    }
+
+    /// Creates a node from the dragged template centered on `world` and
+    /// selects it.
+    pub(super) fn drop_template(&mut self, world: Point){
+        if let Some(template) = self.template_drag.take() {
+            self.unselect_all_nodes();
+            self.unselect_all_edges();
+            self.unselect_all_groups();
+            let mut node = template.node_at(world);
+            node.color = node.color.or(self.default_node_color);
+            node.set_selected(true);
+            self.add_node(node);
+        }
+    }
+
+    /// Draws the outline of the node a drop would create, under the
+    /// cursor, in screen space.
+    pub(super) fn draw_template_preview(&self, frame: &mut Frame, bounds: Rectangle, cursor: Cursor){
+        let (template, position) = match (&self.template_drag, cursor.position_in(&bounds)) {
+            (Some(template), Some(position)) => (template, position),
+            _ => return,
+        };
+        let size = Size::new(template.size.width * self.scaling, template.size.height * self.scaling);
+        let outline = template.shape.path(Rectangle{
+            x: position.x - size.width / 2.0,
+            y: position.y - size.height / 2.0,
+            width: size.width,
+            height: size.height,
+        });
+        let color = template.color.or(self.default_node_color).unwrap_or(self.theme.node);
+        frame.fill(&outline, Color{ a: 0.5, ..color });
+        frame.stroke(&outline, Stroke{
+            color: self.theme.selection,
+            width: 1.5,
+            ..Stroke::default()
+        });
+    }
+}
@@ -0,0 +1,95 @@
+use iced::Color;
+
+/// Colors used to draw the network.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme{
+    pub background: Color,
+    /// Fill of nodes that don't have a color of their own.
+    pub node: Color,
+    /// Text of labels, icons and overlays.
+    pub label: Color,
+    /// Edges and group outlines.
+    pub line: Color,
+    /// Background grid.
+    pub grid: Color,
+    /// Outline of selected nodes, groups and edges.
+    pub selection: Color,
+    /// Freehand annotations.
+    pub annotation: Color,
+}
+
+impl Theme{
+    pub const DARK: Theme = Theme{
+        background: Color::from_rgb(0x04 as f32 / 255.0, 0x44 as f32 / 255.0, 0x48 as f32 / 255.0),
+        node: Color::BLACK,
+        label: Color::WHITE,
+        line: Color::WHITE,
+        grid: Color::from_rgba(1.0, 1.0, 1.0, 0.1),
+        selection: Color::from_rgb(1., 0., 0.),
+        annotation: Color::from_rgb(1.0, 0.85, 0.2),
+    };
+
+    pub const LIGHT: Theme = Theme{
+        background: Color::from_rgb(0.96, 0.96, 0.94),
+        node: Color::from_rgb(0.80, 0.85, 0.90),
+        label: Color::from_rgb(0.10, 0.10, 0.12),
+        line: Color::from_rgb(0.25, 0.25, 0.30),
+        grid: Color::from_rgba(0.0, 0.0, 0.0, 0.1),
+        selection: Color::from_rgb(0.85, 0.20, 0.10),
+        annotation: Color::from_rgb(0.10, 0.40, 0.85),
+    };
+
+    /// The other preset: light after dark and dark after anything else.
+    pub fn toggled(&self) -> Theme{
+        if *self == Theme::DARK {
+            Theme::LIGHT
+        } else {
+            Theme::DARK
+        }
+    }
+
+    /// Opacity of things dimmed by a filter.
+    const DIMMED_ALPHA: f32 = 0.25;
+
+    /// The theme with everything but the background faded, for drawing
+    /// what a filter leaves out.
+    pub fn dimmed(&self) -> Theme{
+        self.with_alpha(Self::DIMMED_ALPHA)
+    }
+
+    /// `color` faded like the colors of a dimmed theme.
+    pub fn faded(color: Color) -> Color{
+        Self::translucent(color, Self::DIMMED_ALPHA)
+    }
+
+    /// The theme with the opacity of the node, label, line and selection
+    /// colors multiplied by `alpha`.
+    pub fn with_alpha(&self, alpha: f32) -> Theme{
+        Theme{
+            background: self.background,
+            node: Self::translucent(self.node, alpha),
+            label: Self::translucent(self.label, alpha),
+            line: Self::translucent(self.line, alpha),
+            grid: self.grid,
+            selection: Self::translucent(self.selection, alpha),
+            annotation: self.annotation,
+        }
+    }
+
+    /// `color` with its opacity multiplied by `alpha`.
+    pub fn translucent(color: Color, alpha: f32) -> Color{
+        Color{ a: color.a * alpha, ..color }
+    }
+
+    /// Background behind labels and overlays, readable over anything drawn
+    /// in this theme.
+    pub fn backdrop(&self) -> Color{
+        Color{ a: 0.7, ..self.background }
+    }
+}
+
+impl Default for Theme{
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
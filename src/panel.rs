@@ -0,0 +1,185 @@
+use iced::{alignment, mouse, Color, Length, Point, Rectangle, Size};
+use iced_native::{event, Clipboard, Element, Event, Layout, Shell, text, Widget};
+use iced_native::layout::{Limits, Node};
+use iced_native::renderer::{Quad, Style};
+
+use crate::network::{NodeShape, NodeTemplate};
+
+/// Palette of node templates along the side of the window. Pressing a
+/// template starts dragging it and releasing the button anywhere ends the
+/// drag; the canvas creates the node if the drop lands on it. The drag
+/// itself is kept by the owner, which passes it back in with `dragging`.
+pub struct Panel<'a, Message>{
+    templates: &'a [NodeTemplate],
+    dragging: Option<usize>,
+    /// Fill of templates without a color of their own.
+    node_color: Color,
+    on_drag: Box<dyn Fn(Option<usize>) -> Message + 'a>,
+}
+
+impl<'a, Message> Panel<'a, Message>{
+    pub const WIDTH: u16 = 90;
+    const SWATCH_SIZE: f32 = 44.0;
+    const LABEL_SIZE: f32 = 14.0;
+    const PADDING: f32 = 10.0;
+    const SPACING: f32 = 12.0;
+    /// Horizontal strips the diamond swatch is made of, since quads can't
+    /// be rotated.
+    const DIAMOND_STRIPS: usize = 16;
+
+    /// `on_drag` is called with the index of the template pressed, and with
+    /// `None` when the button is released during a drag.
+    pub fn new(
+        templates: &'a [NodeTemplate],
+        dragging: Option<usize>,
+        node_color: Color,
+        on_drag: impl Fn(Option<usize>) -> Message + 'a,
+    ) -> Self{
+        Panel{ templates, dragging, node_color, on_drag: Box::new(on_drag) }
+    }
+
+    /// Area taken by the template at `index`: its swatch and the label
+    /// under it.
+    fn item_bounds(bounds: Rectangle, index: usize) -> Rectangle{
+        let height = Self::SWATCH_SIZE + Self::LABEL_SIZE + 4.0;
+        Rectangle{
+            x: bounds.x,
+            y: bounds.y + Self::PADDING + index as f32 * (height + Self::SPACING),
+            width: bounds.width,
+            height,
+        }
+    }
+
+    fn template_at(&self, bounds: Rectangle, position: Point) -> Option<usize>{
+        (0..self.templates.len()).find(|i| Self::item_bounds(bounds, *i).contains(position))
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Panel<'a, Message>
+    where Renderer: text::Renderer,
+{
+    fn width(&self) -> Length {
+        Length::Units(Self::WIDTH)
+    }
+
+    fn height(&self) -> Length {
+        Length::Fill
+    }
+
+    fn layout(&self, _renderer: &Renderer, limits: &Limits) -> Node {
+        let limits = limits.width(Length::Units(Self::WIDTH)).height(Length::Fill);
+        Node::new(limits.resolve(Size::ZERO))
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                match self.template_at(layout.bounds(), cursor_position) {
+                    Some(index) => {
+                        shell.publish((self.on_drag)(Some(index)));
+                        event::Status::Captured
+                    }
+                    None => event::Status::Ignored,
+                }
+            }
+            // Not captured: the canvas needs the release to place the node.
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) if self.dragging.is_some() => {
+                shell.publish((self.on_drag)(None));
+                event::Status::Ignored
+            }
+            _ => event::Status::Ignored,
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        if self.dragging.is_some() {
+            mouse::Interaction::Grabbing
+        } else if self.template_at(layout.bounds(), cursor_position).is_some() {
+            mouse::Interaction::Grab
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+
+    fn draw(&self, renderer: &mut Renderer, style: &Style, layout: Layout<'_>, _cursor_position: Point, _viewport: &Rectangle) {
+        let bounds = layout.bounds();
+        for (i, template) in self.templates.iter().enumerate() {
+            let item = Self::item_bounds(bounds, i);
+            let swatch = Rectangle{
+                x: item.center_x() - Self::SWATCH_SIZE / 2.0,
+                y: item.y,
+                width: Self::SWATCH_SIZE,
+                height: Self::SWATCH_SIZE,
+            };
+            let fill = template.color.unwrap_or(self.node_color);
+            let border_color = if self.dragging == Some(i) { Color::from_rgb(1.0, 0.0, 0.0) } else { style.text_color };
+            match template.shape {
+                NodeShape::Diamond => {
+                    let strips = Self::DIAMOND_STRIPS;
+                    let height = swatch.height / strips as f32;
+                    for strip in 0..strips {
+                        let middle = (strip as f32 + 0.5) / strips as f32;
+                        let width = swatch.width * (1.0 - (2.0 * middle - 1.0).abs());
+                        renderer.fill_quad(Quad{
+                            bounds: Rectangle{
+                                x: swatch.center_x() - width / 2.0,
+                                y: swatch.y + strip as f32 * height,
+                                width,
+                                height,
+                            },
+                            border_radius: 0.0,
+                            border_width: 0.0,
+                            border_color: Color::TRANSPARENT,
+                        }, fill);
+                    }
+                }
+                shape => {
+                    let border_radius = if shape == NodeShape::Ellipse { Self::SWATCH_SIZE / 2.0 } else { 2.0 };
+                    renderer.fill_quad(Quad{
+                        bounds: swatch,
+                        border_radius,
+                        border_width: 1.5,
+                        border_color,
+                    }, fill);
+                }
+            }
+            renderer.fill_text(text::Text{
+                content: &template.name,
+                bounds: Rectangle{
+                    x: item.center_x(),
+                    y: swatch.y + swatch.height + 2.0,
+                    width: item.width,
+                    height: Self::LABEL_SIZE,
+                },
+                size: Self::LABEL_SIZE,
+                color: style.text_color,
+                font: Default::default(),
+                horizontal_alignment: alignment::Horizontal::Center,
+                vertical_alignment: alignment::Vertical::Top,
+            });
+        }
+    }
+}
+
+impl<'a, Message, Renderer> From<Panel<'a, Message>> for Element<'a, Message, Renderer>
+    where Renderer: text::Renderer,
+          Message: 'a,
+{
+    fn from(panel: Panel<'a, Message>) -> Self {
+        Element::new(panel)
+    }
+}
@@ -0,0 +1,298 @@
+use iced::{button, text_input, Button, Color, Column, Element, Length, Rectangle, Row, Text, TextInput};
+
+use crate::network::{Network, Node};
+
+/// Side panel showing the editable properties of the selected node.
+/// Changes are applied to the network as soon as a field holds a valid
+/// value; fields the user isn't editing follow the node.
+pub struct PropertiesPanel{
+    node_id: Option<u32>,
+    label: Field,
+    color: Field,
+    x: Field,
+    y: Field,
+    width: Field,
+    height: Field,
+    rotation: Field,
+    opacity: Field,
+    tags: Field,
+    metadata: Vec<MetadataRow>,
+    new_key: Field,
+    new_value: Field,
+    add: button::State,
+}
+
+#[derive(Debug, Clone)]
+pub enum PropertiesMessage{
+    Label(String),
+    /// `#rrggbb`, or empty for the theme's node color.
+    Color(String),
+    X(String),
+    Y(String),
+    Width(String),
+    Height(String),
+    /// In degrees.
+    Rotation(String),
+    /// From 0 to 1.
+    Opacity(String),
+    /// Comma-separated.
+    Tags(String),
+    MetadataValue(usize, String),
+    RemoveMetadata(usize),
+    NewKey(String),
+    NewValue(String),
+    AddMetadata,
+}
+
+#[derive(Default)]
+struct Field{
+    state: text_input::State,
+    text: String,
+}
+
+struct MetadataRow{
+    key: String,
+    value: Field,
+    remove: button::State,
+}
+
+impl Field{
+    /// Shows `value` unless the user is typing in the field.
+    fn follow(&mut self, value: String){
+        if !self.state.is_focused() {
+            self.text = value;
+        }
+    }
+}
+
+impl PropertiesPanel{
+    pub const WIDTH: u16 = 260;
+    const TEXT_SIZE: u16 = 16;
+
+    pub fn new() -> Self{
+        PropertiesPanel{
+            node_id: None,
+            label: Field::default(),
+            color: Field::default(),
+            x: Field::default(),
+            y: Field::default(),
+            width: Field::default(),
+            height: Field::default(),
+            rotation: Field::default(),
+            opacity: Field::default(),
+            tags: Field::default(),
+            metadata: Vec::new(),
+            new_key: Field::default(),
+            new_value: Field::default(),
+            add: button::State::new(),
+        }
+    }
+
+    /// Refreshes the fields from the selected node.
+    fn sync(&mut self, node: &Node){
+        if self.node_id != Some(node.id()) {
+            *self = PropertiesPanel{ node_id: Some(node.id()), ..PropertiesPanel::new() };
+        }
+        let bounds = node.bounds();
+        self.label.follow(node.label().to_string());
+        self.color.follow(node.color().map(format_color).unwrap_or_default());
+        self.x.follow(bounds.x.to_string());
+        self.y.follow(bounds.y.to_string());
+        self.width.follow(bounds.width.to_string());
+        self.height.follow(bounds.height.to_string());
+        self.rotation.follow(node.rotation().to_degrees().round().to_string());
+        self.opacity.follow(node.alpha().to_string());
+        self.tags.follow(node.tags().join(", "));
+
+        let mut keys: Vec<&String> = node.metadata().keys().collect();
+        keys.sort();
+        if !keys.iter().copied().eq(self.metadata.iter().map(|row| &row.key)) {
+            self.metadata = keys.into_iter()
+                .map(|key| MetadataRow{
+                    key: key.clone(),
+                    value: Field::default(),
+                    remove: button::State::new(),
+                })
+                .collect();
+        }
+        for row in self.metadata.iter_mut() {
+            row.value.follow(node.metadata()[&row.key].clone());
+        }
+    }
+
+    pub fn update(&mut self, network: &mut Network, message: PropertiesMessage){
+        let node = match network.selected_node() {
+            Some(node) => node,
+            None => return,
+        };
+        let (id, bounds) = (node.id(), node.bounds());
+        match message {
+            PropertiesMessage::Label(text) => {
+                network.set_node_label(id, text.clone());
+                self.label.text = text;
+            }
+            PropertiesMessage::Color(text) => {
+                if text.trim().is_empty() {
+                    network.set_node_color(id, None);
+                } else if let Some(color) = parse_color(&text) {
+                    network.set_node_color(id, Some(color));
+                }
+                self.color.text = text;
+            }
+            PropertiesMessage::X(text) => {
+                if let Some(x) = text.trim().parse().ok().filter(|x: &f32| x.is_finite()) {
+                    network.set_node_bounds(id, Rectangle{ x, ..bounds });
+                }
+                self.x.text = text;
+            }
+            PropertiesMessage::Y(text) => {
+                if let Some(y) = text.trim().parse().ok().filter(|y: &f32| y.is_finite()) {
+                    network.set_node_bounds(id, Rectangle{ y, ..bounds });
+                }
+                self.y.text = text;
+            }
+            PropertiesMessage::Width(text) => {
+                if let Some(width) = text.trim().parse().ok().filter(|width: &f32| width.is_finite() && *width > 0.0) {
+                    network.set_node_bounds(id, Rectangle{ width, ..bounds });
+                }
+                self.width.text = text;
+            }
+            PropertiesMessage::Height(text) => {
+                if let Some(height) = text.trim().parse().ok().filter(|height: &f32| height.is_finite() && *height > 0.0) {
+                    network.set_node_bounds(id, Rectangle{ height, ..bounds });
+                }
+                self.height.text = text;
+            }
+            PropertiesMessage::Rotation(text) => {
+                if let Some(degrees) = text.trim().parse::<f32>().ok().filter(|degrees| degrees.is_finite()) {
+                    network.set_node_rotation(id, degrees.to_radians());
+                }
+                self.rotation.text = text;
+            }
+            PropertiesMessage::Opacity(text) => {
+                if let Some(alpha) = text.trim().parse::<f32>().ok().filter(|alpha| alpha.is_finite()) {
+                    network.set_node_alpha(id, alpha);
+                }
+                self.opacity.text = text;
+            }
+            PropertiesMessage::Tags(text) => {
+                network.set_node_tags(id, text.split(',').map(String::from).collect());
+                self.tags.text = text;
+            }
+            PropertiesMessage::MetadataValue(index, text) => {
+                if let Some(row) = self.metadata.get_mut(index) {
+                    network.set_node_metadata(id, row.key.clone(), Some(text.clone()));
+                    row.value.text = text;
+                }
+            }
+            PropertiesMessage::RemoveMetadata(index) => {
+                if index < self.metadata.len() {
+                    let row = self.metadata.remove(index);
+                    network.set_node_metadata(id, row.key, None);
+                }
+            }
+            PropertiesMessage::NewKey(text) => self.new_key.text = text,
+            PropertiesMessage::NewValue(text) => self.new_value.text = text,
+            PropertiesMessage::AddMetadata => {
+                let key = self.new_key.text.trim().to_string();
+                if !key.is_empty() {
+                    network.set_node_metadata(id, key, Some(std::mem::take(&mut self.new_value.text)));
+                    self.new_key.text.clear();
+                }
+            }
+        }
+    }
+
+    pub fn view(&mut self, network: &Network) -> Element<'_, PropertiesMessage>{
+        let mut content = Column::new()
+            .width(Length::Units(Self::WIDTH))
+            .padding(10)
+            .spacing(6);
+        let node = match network.selected_node() {
+            Some(node) => node,
+            None => {
+                self.node_id = None;
+                let message = match network.selected_node_count() {
+                    0 => String::from("Select a node to see its properties."),
+                    count => format!("{} nodes selected. Select a single node to see its properties.", count),
+                };
+                return content.push(Text::new(message).size(Self::TEXT_SIZE)).into();
+            }
+        };
+        self.sync(node);
+
+        let size = Self::TEXT_SIZE;
+        content = content
+            .push(Text::new(format!("Node {}", node.id())).size(size + 4))
+            .push(field("Label", "", &mut self.label, PropertiesMessage::Label))
+            .push(field("Color", "#rrggbb", &mut self.color, PropertiesMessage::Color))
+            .push(field("X", "", &mut self.x, PropertiesMessage::X))
+            .push(field("Y", "", &mut self.y, PropertiesMessage::Y))
+            .push(field("Width", "", &mut self.width, PropertiesMessage::Width))
+            .push(field("Height", "", &mut self.height, PropertiesMessage::Height))
+            .push(field("Rotation", "degrees", &mut self.rotation, PropertiesMessage::Rotation))
+            .push(field("Opacity", "0 to 1", &mut self.opacity, PropertiesMessage::Opacity))
+            .push(field("Tags", "a, b", &mut self.tags, PropertiesMessage::Tags))
+            .push(Text::new("Metadata").size(size + 2));
+        for (i, row) in self.metadata.iter_mut().enumerate() {
+            content = content.push(Row::new()
+                .spacing(6)
+                .push(Text::new(row.key.as_str()).size(size).width(Length::Units(80)))
+                .push(TextInput::new(&mut row.value.state, "", &row.value.text, move |text| {
+                    PropertiesMessage::MetadataValue(i, text)
+                }).size(size).padding(4))
+                .push(Button::new(&mut row.remove, Text::new("×").size(size))
+                    .on_press(PropertiesMessage::RemoveMetadata(i))));
+        }
+        content
+            .push(Row::new()
+                .spacing(6)
+                .push(TextInput::new(&mut self.new_key.state, "key", &self.new_key.text, PropertiesMessage::NewKey)
+                    .size(size)
+                    .padding(4)
+                    .width(Length::Units(80))
+                    .on_submit(PropertiesMessage::AddMetadata))
+                .push(TextInput::new(&mut self.new_value.state, "value", &self.new_value.text, PropertiesMessage::NewValue)
+                    .size(size)
+                    .padding(4)
+                    .on_submit(PropertiesMessage::AddMetadata))
+                .push(Button::new(&mut self.add, Text::new("+").size(size))
+                    .on_press(PropertiesMessage::AddMetadata)))
+            .into()
+    }
+}
+
+impl Default for PropertiesPanel{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A labelled text field.
+fn field<'a>(
+    name: &str,
+    placeholder: &str,
+    field: &'a mut Field,
+    on_change: fn(String) -> PropertiesMessage,
+) -> Row<'a, PropertiesMessage>{
+    let size = PropertiesPanel::TEXT_SIZE;
+    Row::new()
+        .spacing(6)
+        .push(Text::new(name).size(size).width(Length::Units(60)))
+        .push(TextInput::new(&mut field.state, placeholder, &field.text, on_change).size(size).padding(4))
+}
+
+/// Parses `#rrggbb` or `rrggbb`.
+fn parse_color(text: &str) -> Option<Color>{
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::from_rgb8(channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn format_color(color: Color) -> String{
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(color.r), channel(color.g), channel(color.b))
+}
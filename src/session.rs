@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Window geometry and view remembered between runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session{
+    /// Top-left corner of the window, in logical pixels.
+    pub window_position: Option<(i32, i32)>,
+    pub window_size: (u32, u32),
+    pub translation: (f32, f32),
+    pub scaling: f32,
+}
+
+impl Session{
+    /// File the session is kept in, next to where the app was started.
+    pub const PATH: &'static str = "sword-session.json";
+
+    /// Reads a session written by `save`. A missing or unreadable file
+    /// gives `None`, since there is nothing useful to do about it.
+    pub fn load(path: impl AsRef<Path>) -> Option<Session>{
+        let src = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&src) {
+            Ok(session) => Some(session),
+            Err(error) => {
+                println!("Ignoring saved session: {}", error);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()>{
+        let src = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        std::fs::write(path, src)
+    }
+
+    /// Where to reopen the window. The monitor layout isn't known before the
+    /// window exists, so a window whose top-left corner was left above or to
+    /// the left of the screen, where its title bar can't be grabbed, is
+    /// centered instead.
+    pub fn on_screen_position(&self) -> Option<(i32, i32)>{
+        self.window_position.filter(|(x, y)| *x >= 0 && *y >= 0)
+    }
+}
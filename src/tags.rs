@@ -0,0 +1,69 @@
+use iced::{button, Button, Column, Element, Length, Text};
+
+use crate::network::Network;
+
+/// Side panel listing the tags used in the network. Clicking a tag dims
+/// the nodes without it; clicking it again shows everything.
+pub struct TagPanel{
+    tags: Vec<(String, button::State)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum TagMessage{
+    Toggle(String),
+}
+
+impl TagPanel{
+    pub const WIDTH: u16 = 260;
+    const TEXT_SIZE: u16 = 16;
+
+    pub fn new() -> Self{
+        TagPanel{ tags: Vec::new() }
+    }
+
+    pub fn update(&mut self, network: &mut Network, message: TagMessage){
+        match message {
+            TagMessage::Toggle(tag) => {
+                let filter = if network.tag_filter() == Some(tag.as_str()) { None } else { Some(tag) };
+                network.set_tag_filter(filter);
+            }
+        }
+    }
+
+    pub fn view(&mut self, network: &Network) -> Element<'_, TagMessage>{
+        // The active filter stays listed after its last node lost the tag,
+        // so it can still be turned off.
+        let mut tags = network.tags();
+        if let Some(filter) = network.tag_filter() {
+            if !tags.iter().any(|tag| tag == filter) {
+                tags.push(filter.to_string());
+                tags.sort();
+            }
+        }
+        if !tags.iter().eq(self.tags.iter().map(|(tag, _)| tag)) {
+            self.tags = tags.into_iter().map(|tag| (tag, button::State::new())).collect();
+        }
+
+        let size = Self::TEXT_SIZE;
+        let mut content = Column::new()
+            .width(Length::Units(Self::WIDTH))
+            .padding(10)
+            .spacing(6)
+            .push(Text::new("Tags").size(size + 2));
+        if self.tags.is_empty() {
+            return content.push(Text::new("No node is tagged.").size(size)).into();
+        }
+        for (tag, state) in self.tags.iter_mut() {
+            let marker = if network.tag_filter() == Some(tag.as_str()) { "●" } else { "○" };
+            content = content.push(Button::new(state, Text::new(format!("{} {}", marker, tag)).size(size))
+                .on_press(TagMessage::Toggle(tag.clone())));
+        }
+        content.into()
+    }
+}
+
+impl Default for TagPanel{
+    fn default() -> Self {
+        Self::new()
+    }
+}